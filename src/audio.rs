@@ -0,0 +1,739 @@
+use hound::{SampleFormat, WavReader};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// Descriptive information about an opened audio source, for display only
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioMetadata {
+    /// Human-readable codec description, e.g. "WAV (16-bit int)"
+    pub codec: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Samples per channel, if the container knows it
+    pub total_samples: Option<usize>,
+}
+
+impl AudioMetadata {
+    /// Format the metadata as a multi-line human-readable block
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = format!(
+            "Codec:       {}\nChannels:    {}\nSample rate: {} Hz",
+            self.codec, self.channels, self.sample_rate
+        );
+        match self.total_samples {
+            Some(total) => {
+                let duration = total as f64 / self.sample_rate.max(1) as f64;
+                out.push_str(&format!("\nSamples:     {}\nDuration:    {:.2} s", total, duration));
+            }
+            None => out.push_str("\nSamples:     unknown"),
+        }
+        out
+    }
+}
+
+/// Abstraction over decodable audio sources
+///
+/// Implementations yield interleaved samples normalized to f32 in `[-1.0, 1.0]`.
+pub trait AudioReader {
+    /// Sample rate, Hz
+    fn sample_rate(&self) -> u32;
+
+    /// Descriptive metadata; the default only knows what the trait exposes
+    fn metadata(&self) -> AudioMetadata {
+        AudioMetadata {
+            codec: "unknown".to_string(),
+            sample_rate: self.sample_rate(),
+            channels: 1,
+            total_samples: self.total_samples(),
+        }
+    }
+
+    /// Total number of samples (across all channels), if the container knows it
+    fn total_samples(&self) -> Option<usize>;
+
+    /// Read normalized samples into `out` until it is full or the stream ends;
+    /// returns the number of samples written (0 at end of stream)
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>>;
+
+    /// Read the samples in `[start_sample, end_sample)` into `out`;
+    /// returns the number of samples written (fewer than requested when
+    /// the stream ends inside the range)
+    ///
+    /// Positions are in samples from the current stream position, so this
+    /// is meant for a fresh reader.
+    #[allow(dead_code)] // Not used by the CLI pipeline yet
+    fn read_range(
+        &mut self,
+        start_sample: u64,
+        end_sample: u64,
+        out: &mut Vec<f32>,
+    ) -> Result<usize, Box<dyn Error>> {
+        if end_sample < start_sample {
+            return Err(format!(
+                "invalid sample range: start {} is past end {}",
+                start_sample, end_sample
+            ).into());
+        }
+        self.skip(start_sample as usize)?;
+        let len = (end_sample - start_sample) as usize;
+        out.resize(len, 0.0);
+        let mut read = 0;
+        while read < len {
+            let n = self.read(&mut out[read..])?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+        out.truncate(read);
+        Ok(read)
+    }
+
+    /// Seek forward by a time offset from the current position, converting
+    /// seconds to samples via the stream's sample rate
+    #[allow(dead_code)] // Library-style API, exercised by tests
+    fn seek_time(&mut self, seconds: f64) -> Result<usize, Box<dyn Error>> {
+        if seconds < 0.0 || !seconds.is_finite() {
+            return Err(format!("seek time must be a non-negative number of seconds (got {})", seconds).into());
+        }
+        self.skip((seconds * self.sample_rate() as f64).round() as usize)
+    }
+
+    /// Skip `count` samples; returns the number actually skipped
+    fn skip(&mut self, count: usize) -> Result<usize, Box<dyn Error>> {
+        let mut scratch = [0.0f32; 4096];
+        let mut remaining = count;
+        while remaining > 0 {
+            let chunk = remaining.min(scratch.len());
+            let read = self.read(&mut scratch[..chunk])?;
+            if read == 0 {
+                break;
+            }
+            remaining -= read;
+        }
+        Ok(count - remaining)
+    }
+}
+
+/// WAV reader backed by hound, decoding integer and f32 sample formats
+///
+/// With a selected channel, frames are deinterleaved and only that
+/// channel's samples are returned.
+pub struct WavAudioReader {
+    reader: WavReader<Box<dyn Read>>,
+    channel: Option<usize>,
+    /// Average all channels of each frame into one mono sample
+    downmix: bool,
+}
+
+impl WavAudioReader {
+    pub fn open(path: &Path, channel: Option<usize>, downmix: bool) -> Result<Self, Box<dyn Error>> {
+        Self::from_reader(Box::new(BufReader::new(File::open(path)?)), channel, downmix)
+    }
+
+    /// Parse a WAV stream from any byte source (stdin, an in-memory buffer);
+    /// the WAV header carries all the metadata a file would provide
+    pub fn from_reader(
+        inner: Box<dyn Read>,
+        channel: Option<usize>,
+        downmix: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        if downmix && channel.is_some() {
+            return Err("downmix cannot be combined with channel selection".into());
+        }
+        let reader = WavReader::new(inner)?;
+        let channels = reader.spec().channels as usize;
+        if let Some(ch) = channel
+            && ch >= channels
+        {
+            return Err(format!(
+                "channel {} requested, but the file has only {} channel(s)",
+                ch, channels
+            ).into());
+        }
+        Ok(Self { reader, channel, downmix })
+    }
+}
+
+/// Fill `out` from an iterator of decoded samples, optionally keeping only
+/// one channel of each interleaved frame; a partial frame at EOF is dropped
+fn fill_from<I>(
+    mut samples: I,
+    out: &mut [f32],
+    channels: usize,
+    channel: Option<usize>,
+) -> Result<usize, Box<dyn Error>>
+where
+    I: Iterator<Item = Result<f32, hound::Error>>,
+{
+    let mut read = 0;
+    match channel {
+        None => {
+            while read < out.len() {
+                match samples.next() {
+                    Some(sample) => {
+                        out[read] = sample?;
+                        read += 1;
+                    }
+                    None => break,
+                }
+            }
+        }
+        Some(ch) => {
+            'frames: while read < out.len() {
+                let mut value = None;
+                for c in 0..channels {
+                    match samples.next() {
+                        Some(sample) => {
+                            let sample = sample?;
+                            if c == ch {
+                                value = Some(sample);
+                            }
+                        }
+                        None => break 'frames,
+                    }
+                }
+                if let Some(v) = value {
+                    out[read] = v;
+                    read += 1;
+                }
+            }
+        }
+    }
+    Ok(read)
+}
+
+/// Average each interleaved frame into a single mono sample;
+/// a partial frame at EOF is dropped
+fn fill_downmix<I>(mut samples: I, out: &mut [f32], channels: usize) -> Result<usize, Box<dyn Error>>
+where
+    I: Iterator<Item = Result<f32, hound::Error>>,
+{
+    let mut read = 0;
+    'frames: while read < out.len() {
+        let mut sum = 0.0f32;
+        for _ in 0..channels {
+            match samples.next() {
+                Some(sample) => sum += sample?,
+                None => break 'frames,
+            }
+        }
+        out[read] = sum / channels.max(1) as f32;
+        read += 1;
+    }
+    Ok(read)
+}
+
+impl AudioReader for WavAudioReader {
+    fn sample_rate(&self) -> u32 {
+        self.reader.spec().sample_rate
+    }
+
+    fn metadata(&self) -> AudioMetadata {
+        let spec = self.reader.spec();
+        let format = match spec.sample_format {
+            SampleFormat::Float => "float",
+            SampleFormat::Int => "int",
+        };
+        AudioMetadata {
+            codec: format!("WAV ({}-bit {})", spec.bits_per_sample, format),
+            sample_rate: spec.sample_rate,
+            channels: spec.channels,
+            total_samples: Some(self.reader.len() as usize / spec.channels.max(1) as usize),
+        }
+    }
+
+    fn total_samples(&self) -> Option<usize> {
+        let total = self.reader.len() as usize;
+        if self.channel.is_some() || self.downmix {
+            // Per-frame sample count when deinterleaving or downmixing
+            Some(total / self.reader.spec().channels.max(1) as usize)
+        } else {
+            Some(total)
+        }
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>> {
+        let spec = self.reader.spec();
+        let channels = spec.channels as usize;
+        match spec.sample_format {
+            SampleFormat::Float => {
+                let samples = self.reader.samples::<f32>();
+                if self.downmix {
+                    fill_downmix(samples, out, channels)
+                } else {
+                    fill_from(samples, out, channels, self.channel)
+                }
+            }
+            SampleFormat::Int => {
+                // Full-scale positive value for the given bit depth
+                // (e.g. 32767 for 16-bit), matching the old i16::MAX scaling
+                let scale = ((1u64 << (spec.bits_per_sample - 1)) - 1) as f32;
+                let samples = self.reader.samples::<i32>().map(|s| s.map(|v| v as f32 / scale));
+                if self.downmix {
+                    fill_downmix(samples, out, channels)
+                } else {
+                    fill_from(samples, out, channels, self.channel)
+                }
+            }
+        }
+    }
+}
+
+/// Reader over an in-memory buffer of normalized f32 samples, so embedders
+/// with audio already in memory can skip the temp-file round trip
+///
+/// For I/Q input the buffer holds interleaved I/Q pairs, exactly like the
+/// scalar stream a file reader would produce.
+pub struct BufferAudioReader<'a> {
+    samples: &'a [f32],
+    sample_rate: u32,
+    pos: usize,
+}
+
+impl<'a> BufferAudioReader<'a> {
+    pub fn new(samples: &'a [f32], sample_rate: u32) -> Self {
+        Self { samples, sample_rate, pos: 0 }
+    }
+}
+
+impl AudioReader for BufferAudioReader<'_> {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn metadata(&self) -> AudioMetadata {
+        AudioMetadata {
+            codec: "memory buffer (f32)".to_string(),
+            sample_rate: self.sample_rate,
+            channels: 1,
+            total_samples: Some(self.samples.len()),
+        }
+    }
+
+    fn total_samples(&self) -> Option<usize> {
+        Some(self.samples.len())
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>> {
+        let n = out.len().min(self.samples.len() - self.pos);
+        out[..n].copy_from_slice(&self.samples[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<usize, Box<dyn Error>> {
+        let n = count.min(self.samples.len() - self.pos);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Decimating reader that block-averages groups of `factor` frames into one,
+/// dividing the effective sample rate by `factor`
+///
+/// Averaging the block acts as a crude low-pass filter, which aliases less
+/// than keeping every Nth sample outright. For I/Q input a frame is `stride`
+/// consecutive scalars and each component is averaged independently.
+/// Intended for fast low-fidelity previews of very large captures.
+pub struct DecimateReader<'a> {
+    inner: &'a mut dyn AudioReader,
+    factor: usize,
+    stride: usize,
+    /// Scratch for one block of `factor` source frames
+    block: Vec<f32>,
+}
+
+impl<'a> DecimateReader<'a> {
+    pub fn new(inner: &'a mut dyn AudioReader, factor: usize, stride: usize) -> Self {
+        let factor = factor.max(1);
+        Self {
+            inner,
+            factor,
+            stride,
+            block: vec![0.0; factor * stride],
+        }
+    }
+}
+
+impl AudioReader for DecimateReader<'_> {
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate() / self.factor as u32
+    }
+
+    fn metadata(&self) -> AudioMetadata {
+        let meta = self.inner.metadata();
+        AudioMetadata {
+            codec: format!("{}, decimated {}x", meta.codec, self.factor),
+            sample_rate: self.sample_rate(),
+            total_samples: meta.total_samples.map(|t| t / self.factor),
+            ..meta
+        }
+    }
+
+    fn total_samples(&self) -> Option<usize> {
+        self.inner
+            .total_samples()
+            .map(|total| (total / self.stride / self.factor) * self.stride)
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>> {
+        let stride = self.stride;
+        let mut produced = 0;
+        'frames: while produced + stride <= out.len() {
+            // A full block of source frames per output frame;
+            // a partial block at EOF is dropped
+            let mut filled = 0;
+            while filled < self.block.len() {
+                let n = self.inner.read(&mut self.block[filled..])?;
+                if n == 0 {
+                    break 'frames;
+                }
+                filled += n;
+            }
+            for c in 0..stride {
+                let sum: f32 = self.block[c..].iter().step_by(stride).sum();
+                out[produced + c] = sum / self.factor as f32;
+            }
+            produced += stride;
+        }
+        Ok(produced)
+    }
+}
+
+/// Streaming linear resampler wrapping another reader, so captures with
+/// different native rates can share one frequency axis
+///
+/// Each output frame at target position `n` is interpolated between the two
+/// source frames straddling `n * native_rate / target_rate`. For I/Q input a
+/// frame is `stride` consecutive scalars and each component is interpolated
+/// independently, keeping pairs intact. Linear interpolation is crude as
+/// resamplers go (no anti-alias filter when downsampling), but adequate for
+/// display purposes.
+pub struct ResampleReader<'a> {
+    inner: &'a mut dyn AudioReader,
+    target_rate: u32,
+    stride: usize,
+    /// Source frames advanced per output frame
+    step: f64,
+    /// Source frames `src_next - 1` and `src_next`
+    prev: Vec<f32>,
+    next: Vec<f32>,
+    src_next: usize,
+    /// Index of the next output frame to produce
+    out_index: usize,
+    primed: bool,
+    eof: bool,
+}
+
+impl<'a> ResampleReader<'a> {
+    pub fn new(inner: &'a mut dyn AudioReader, target_rate: u32, stride: usize) -> Self {
+        let step = inner.sample_rate() as f64 / target_rate.max(1) as f64;
+        Self {
+            inner,
+            target_rate,
+            stride,
+            step,
+            prev: vec![0.0; stride],
+            next: vec![0.0; stride],
+            src_next: 0,
+            out_index: 0,
+            primed: false,
+            eof: false,
+        }
+    }
+
+    /// Read one full source frame into `next`; false at end of stream
+    /// (a partial frame at EOF is dropped)
+    fn fill_next(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut filled = 0;
+        while filled < self.stride {
+            let n = self.inner.read(&mut self.next[filled..])?;
+            if n == 0 {
+                return Ok(false);
+            }
+            filled += n;
+        }
+        Ok(true)
+    }
+}
+
+impl AudioReader for ResampleReader<'_> {
+    fn sample_rate(&self) -> u32 {
+        self.target_rate
+    }
+
+    fn metadata(&self) -> AudioMetadata {
+        let meta = self.inner.metadata();
+        AudioMetadata {
+            codec: format!("{}, resampled from {} Hz", meta.codec, meta.sample_rate),
+            sample_rate: self.target_rate,
+            total_samples: self.total_samples().map(|t| t / self.stride),
+            ..meta
+        }
+    }
+
+    fn total_samples(&self) -> Option<usize> {
+        self.inner.total_samples().map(|total| {
+            let src_frames = total / self.stride;
+            if src_frames < 2 {
+                return 0;
+            }
+            // Output frame n needs source frames floor(n * step) and the one
+            // after it, so the last valid n satisfies n * step < src_frames - 1
+            let out_frames = ((src_frames - 1) as f64 / self.step).ceil() as usize;
+            out_frames * self.stride
+        })
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>> {
+        if self.eof {
+            return Ok(0);
+        }
+        if !self.primed {
+            // Source frames 0 and 1 seed the interpolation window
+            if !self.fill_next()? {
+                self.eof = true;
+                return Ok(0);
+            }
+            self.prev.copy_from_slice(&self.next);
+            if !self.fill_next()? {
+                self.eof = true;
+                return Ok(0);
+            }
+            self.src_next = 1;
+            self.primed = true;
+        }
+        let stride = self.stride;
+        let mut produced = 0;
+        'frames: while produced + stride <= out.len() {
+            let pos = self.out_index as f64 * self.step;
+            let src = pos.floor() as usize;
+            let frac = (pos - src as f64) as f32;
+            // Advance until `prev`/`next` are source frames src and src + 1
+            while self.src_next < src + 1 {
+                std::mem::swap(&mut self.prev, &mut self.next);
+                if !self.fill_next()? {
+                    self.eof = true;
+                    break 'frames;
+                }
+                self.src_next += 1;
+            }
+            for c in 0..stride {
+                out[produced + c] = self.prev[c] + frac * (self.next[c] - self.prev[c]);
+            }
+            produced += stride;
+            self.out_index += 1;
+        }
+        Ok(produced)
+    }
+}
+
+/// Sample encoding of a headerless raw capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RawSampleFormat {
+    I16,
+    F32,
+}
+
+impl RawSampleFormat {
+    /// Size of one encoded sample in bytes
+    fn sample_bytes(self) -> usize {
+        match self {
+            RawSampleFormat::I16 => 2,
+            RawSampleFormat::F32 => 4,
+        }
+    }
+}
+
+/// Byte order of a headerless raw capture
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RawEndian {
+    Le,
+    Be,
+}
+
+/// Component order of interleaved I/Q pairs in a raw capture
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IqOrder {
+    /// I first, then Q (the common convention)
+    Iq,
+    /// Q first, then I; the reader swaps each pair back
+    Qi,
+}
+
+/// Metadata for raw input that a headerless file cannot carry itself,
+/// so it must come from the command line
+#[derive(Copy, Clone, Debug)]
+pub struct RawInputParams {
+    pub sample_rate: u32,
+    pub sample_format: RawSampleFormat,
+    pub endian: RawEndian,
+    pub iq_order: IqOrder,
+}
+
+/// Reader for headerless raw capture files (`.raw`/`.iqw`)
+///
+/// The file is a flat stream of `i16` or `f32` samples; for I/Q captures
+/// the I and Q components are interleaved. Sample rate, encoding, byte
+/// order and pair order are taken from [`RawInputParams`] since there is
+/// no header.
+pub struct RawIqReader {
+    reader: Box<dyn Read>,
+    sample_rate: u32,
+    sample_format: RawSampleFormat,
+    endian: RawEndian,
+    iq_order: IqOrder,
+    /// Known only for file input; a piped stream has no length up front
+    total_samples: Option<usize>,
+}
+
+impl RawIqReader {
+    pub fn open(path: &Path, params: RawInputParams) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let bytes = file.metadata()?.len() as usize;
+        let mut reader = Self::from_stream(Box::new(BufReader::new(file)), params)?;
+        reader.total_samples = Some(bytes / params.sample_format.sample_bytes());
+        Ok(reader)
+    }
+
+    /// Raw samples from an unseekable stream of unknown length (e.g. stdin)
+    pub fn from_stream(inner: Box<dyn Read>, params: RawInputParams) -> Result<Self, Box<dyn Error>> {
+        if params.sample_rate == 0 {
+            return Err("raw input requires a non-zero sample rate".into());
+        }
+        Ok(Self {
+            reader: inner,
+            sample_rate: params.sample_rate,
+            sample_format: params.sample_format,
+            endian: params.endian,
+            iq_order: params.iq_order,
+            total_samples: None,
+        })
+    }
+}
+
+impl AudioReader for RawIqReader {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn metadata(&self) -> AudioMetadata {
+        let format = match self.sample_format {
+            RawSampleFormat::I16 => "i16",
+            RawSampleFormat::F32 => "f32",
+        };
+        let endian = match self.endian {
+            RawEndian::Le => "le",
+            RawEndian::Be => "be",
+        };
+        AudioMetadata {
+            codec: format!("raw ({} {}, headerless)", format, endian),
+            sample_rate: self.sample_rate,
+            channels: 1,
+            total_samples: self.total_samples,
+        }
+    }
+
+    fn total_samples(&self) -> Option<usize> {
+        self.total_samples
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn Error>> {
+        let sample_bytes = self.sample_format.sample_bytes();
+        let mut bytes = vec![0u8; out.len() * sample_bytes];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            let n = self.reader.read(&mut bytes[filled..])?;
+            if n == 0 {
+                break;
+            }
+            filled += n;
+        }
+        // A trailing partial sample at EOF is dropped; in swapped pair
+        // order a trailing lone component is dropped with it
+        let mut count = filled / sample_bytes;
+        if self.iq_order == IqOrder::Qi {
+            count &= !1;
+        }
+        for (slot, chunk) in out.iter_mut().zip(bytes.chunks_exact(sample_bytes)).take(count) {
+            *slot = match (self.sample_format, self.endian) {
+                (RawSampleFormat::I16, RawEndian::Le) => {
+                    i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / i16::MAX as f32
+                }
+                (RawSampleFormat::I16, RawEndian::Be) => {
+                    i16::from_be_bytes([chunk[0], chunk[1]]) as f32 / i16::MAX as f32
+                }
+                (RawSampleFormat::F32, RawEndian::Le) => {
+                    f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                }
+                (RawSampleFormat::F32, RawEndian::Be) => {
+                    f32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                }
+            };
+        }
+        if self.iq_order == IqOrder::Qi {
+            for pair in out[..count].chunks_exact_mut(2) {
+                pair.swap(0, 1);
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// Open an audio file, dispatching on the file extension
+///
+/// The special path `-` reads from stdin instead of a file.
+/// FLAC is recognized but not decodable in this build: a FLAC decoder
+/// dependency is not vendored. Raw capture extensions (and `raw_input`
+/// supplied for any extension) go through [`RawIqReader`]. Unknown
+/// extensions are tried as WAV so arbitrarily named files keep working.
+pub fn create_audio_reader(
+    path: &Path,
+    channel: Option<usize>,
+    downmix: bool,
+    raw_input: Option<RawInputParams>,
+) -> Result<Box<dyn AudioReader>, Box<dyn Error>> {
+    // `-` reads from stdin. With no extension to probe, raw params act as
+    // the required format hint; without them the stream is parsed as WAV,
+    // whose header is self-describing.
+    if path.as_os_str() == "-" {
+        let stdin: Box<dyn Read> = Box::new(BufReader::new(std::io::stdin()));
+        if let Some(raw) = raw_input {
+            if channel.is_some() {
+                return Err("channel selection is not supported for raw input".into());
+            }
+            if downmix {
+                return Err("downmix is not supported for raw input".into());
+            }
+            return Ok(Box::new(RawIqReader::from_stream(stdin, raw)?));
+        }
+        return Ok(Box::new(WavAudioReader::from_reader(stdin, channel, downmix)?));
+    }
+
+    let ext = path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if raw_input.is_some() || matches!(ext.as_str(), "raw" | "iqw") {
+        let raw = raw_input.ok_or("raw input has no header: --sample-rate is required")?;
+        if channel.is_some() {
+            return Err("channel selection is not supported for raw input".into());
+        }
+        if downmix {
+            return Err("downmix is not supported for raw input".into());
+        }
+        return Ok(Box::new(RawIqReader::open(path, raw)?));
+    }
+    match ext.as_str() {
+        "flac" => Err("FLAC decoding is not supported in this build (no vendored decoder)".into()),
+        _ => Ok(Box::new(WavAudioReader::open(path, channel, downmix)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    include!("audio_tests.rs");
+}
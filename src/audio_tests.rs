@@ -0,0 +1,486 @@
+#[allow(unused_imports)]
+use super::*;
+
+/// Write a short 440 Hz mono WAV in the given sample format
+fn write_tone_wav(name: &str, sample_format: SampleFormat, bits_per_sample: u16) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5;
+        match sample_format {
+            SampleFormat::Float => writer.write_sample(sample).unwrap(),
+            SampleFormat::Int => writer.write_sample((sample * i16::MAX as f32) as i16).unwrap(),
+        }
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_wav_reader_metadata() {
+    let path = write_tone_wav("sgvr_audio_meta.wav", SampleFormat::Int, 16);
+    let reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    assert_eq!(reader.sample_rate(), 8000);
+    assert_eq!(reader.total_samples(), Some(8000));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_wav_reader_f32_samples_normalized() {
+    let path = write_tone_wav("sgvr_audio_f32.wav", SampleFormat::Float, 32);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    let mut buffer = vec![0.0f32; 8000];
+    let read = reader.read(&mut buffer).unwrap();
+    assert_eq!(read, 8000);
+    assert!(buffer.iter().all(|s| s.abs() <= 1.0));
+    // The tone has amplitude 0.5, so the peak should be close to it
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((peak - 0.5).abs() < 0.01);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_wav_reader_int_matches_float() {
+    let int_path = write_tone_wav("sgvr_audio_i16.wav", SampleFormat::Int, 16);
+    let float_path = write_tone_wav("sgvr_audio_f32b.wav", SampleFormat::Float, 32);
+
+    let mut int_reader = WavAudioReader::open(&int_path, None, false).unwrap();
+    let mut float_reader = WavAudioReader::open(&float_path, None, false).unwrap();
+
+    let mut int_samples = vec![0.0f32; 8000];
+    let mut float_samples = vec![0.0f32; 8000];
+    int_reader.read(&mut int_samples).unwrap();
+    float_reader.read(&mut float_samples).unwrap();
+
+    for (a, b) in int_samples.iter().zip(float_samples.iter()) {
+        assert!((a - b).abs() < 1.0e-4);
+    }
+
+    std::fs::remove_file(&int_path).ok();
+    std::fs::remove_file(&float_path).ok();
+}
+
+#[test]
+fn test_reader_skip() {
+    let path = write_tone_wav("sgvr_audio_skip.wav", SampleFormat::Int, 16);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    assert_eq!(reader.skip(5000).unwrap(), 5000);
+    let mut buffer = vec![0.0f32; 8000];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 3000);
+    // Skipping past the end reports the truncated count
+    assert_eq!(reader.skip(100).unwrap(), 0);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_read_range() {
+    let path = write_tone_wav("sgvr_audio_range.wav", SampleFormat::Int, 16);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    let mut range = Vec::new();
+    assert_eq!(reader.read_range(2000, 5000, &mut range).unwrap(), 3000);
+    assert_eq!(range.len(), 3000);
+
+    // Samples match a sequential read at the same offset
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+    let mut all = vec![0.0f32; 8000];
+    reader.read(&mut all).unwrap();
+    assert_eq!(range, all[2000..5000]);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_read_range_truncated_at_eof() {
+    let path = write_tone_wav("sgvr_audio_range_eof.wav", SampleFormat::Int, 16);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    let mut range = Vec::new();
+    assert_eq!(reader.read_range(7000, 9000, &mut range).unwrap(), 1000);
+    assert_eq!(range.len(), 1000);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_create_audio_reader_rejects_flac() {
+    let err = match create_audio_reader(Path::new("missing.flac"), None, false, None) {
+        Err(e) => e,
+        Ok(_) => panic!("FLAC input should be rejected"),
+    };
+    assert!(err.to_string().contains("FLAC"));
+}
+
+#[test]
+fn test_wav_metadata_pretty_string() {
+    let path = write_tone_wav("sgvr_audio_pretty.wav", SampleFormat::Int, 16);
+    let reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    let metadata = reader.metadata();
+    assert_eq!(
+        metadata,
+        AudioMetadata {
+            codec: "WAV (16-bit int)".to_string(),
+            sample_rate: 8000,
+            channels: 1,
+            total_samples: Some(8000),
+        }
+    );
+    assert_eq!(
+        metadata.to_pretty_string(),
+        "Codec:       WAV (16-bit int)\n\
+         Channels:    1\n\
+         Sample rate: 8000 Hz\n\
+         Samples:     8000\n\
+         Duration:    1.00 s"
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_raw_reader_f32_metadata_and_samples() {
+    let path = std::env::temp_dir().join("sgvr_audio_raw_f32.iqw");
+    let values = [0.5f32, -0.5, 0.25, -0.25];
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    std::fs::write(&path, &bytes).unwrap();
+
+    let params = RawInputParams { sample_rate: 48000, sample_format: RawSampleFormat::F32, endian: RawEndian::Le, iq_order: IqOrder::Iq };
+    let mut reader = RawIqReader::open(&path, params).unwrap();
+    // Metadata comes straight from the CLI-provided parameters
+    assert_eq!(reader.sample_rate(), 48000);
+    assert_eq!(reader.total_samples(), Some(4));
+
+    let mut out = vec![0.0f32; 8];
+    assert_eq!(reader.read(&mut out).unwrap(), 4);
+    assert_eq!(out[..4], values);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_raw_reader_i16_normalized() {
+    let path = std::env::temp_dir().join("sgvr_audio_raw_i16.raw");
+    let values = [i16::MAX, i16::MIN + 1, 0, i16::MAX / 2];
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    std::fs::write(&path, &bytes).unwrap();
+
+    let params = RawInputParams { sample_rate: 8000, sample_format: RawSampleFormat::I16, endian: RawEndian::Le, iq_order: IqOrder::Iq };
+    let mut reader = RawIqReader::open(&path, params).unwrap();
+    let mut out = vec![0.0f32; 4];
+    assert_eq!(reader.read(&mut out).unwrap(), 4);
+    assert!((out[0] - 1.0).abs() < 1e-6);
+    assert!((out[1] + 1.0).abs() < 1e-6);
+    assert_eq!(out[2], 0.0);
+    assert!((out[3] - 0.5).abs() < 1e-4);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_raw_extension_requires_sample_rate() {
+    let err = match create_audio_reader(Path::new("capture.iqw"), None, false, None) {
+        Err(e) => e,
+        Ok(_) => panic!("raw input without a sample rate should be rejected"),
+    };
+    assert!(err.to_string().contains("--sample-rate"));
+}
+
+/// Write a stereo WAV with distinct constant levels per channel
+fn write_stereo_wav(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for _ in 0..1000 {
+        writer.write_sample((0.25 * i16::MAX as f32) as i16).unwrap();
+        writer.write_sample((-0.5 * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_stereo_channel_selection() {
+    let path = write_stereo_wav("sgvr_audio_stereo.wav");
+
+    let mut left = WavAudioReader::open(&path, Some(0), false).unwrap();
+    assert_eq!(left.total_samples(), Some(1000));
+    let mut buffer = vec![0.0f32; 1000];
+    assert_eq!(left.read(&mut buffer).unwrap(), 1000);
+    assert!(buffer.iter().all(|s| (s - 0.25).abs() < 0.001));
+
+    let mut right = WavAudioReader::open(&path, Some(1), false).unwrap();
+    let mut buffer = vec![0.0f32; 1000];
+    assert_eq!(right.read(&mut buffer).unwrap(), 1000);
+    assert!(buffer.iter().all(|s| (s + 0.5).abs() < 0.001));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_channel_out_of_range_is_an_error() {
+    let path = write_stereo_wav("sgvr_audio_stereo_oob.wav");
+    let err = match WavAudioReader::open(&path, Some(2), false) {
+        Err(e) => e,
+        Ok(_) => panic!("out-of-range channel should be rejected"),
+    };
+    assert!(err.to_string().contains("only 2 channel"));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_wav_from_in_memory_stream() {
+    // A WAV fed through a cursor exercises the same path stdin uses:
+    // no file, no extension, everything comes from the stream's header
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::new(&mut bytes, spec).unwrap();
+    for t in 0..500 {
+        let sample = (t as f32 / 500.0 - 0.5) * 0.4;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+    bytes.set_position(0);
+
+    let mut reader = WavAudioReader::from_reader(Box::new(bytes), None, false).unwrap();
+    assert_eq!(reader.sample_rate(), 8000);
+    assert_eq!(reader.total_samples(), Some(500));
+
+    let mut buffer = vec![0.0f32; 600];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 500);
+    assert!((buffer[0] - -0.2).abs() < 0.001);
+}
+
+#[test]
+fn test_raw_stream_has_unknown_length() {
+    // Raw samples from a stream: length unknown, reads until EOF
+    let samples: Vec<u8> = [0.5f32, -0.5, 0.25, -0.25]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let params = RawInputParams {
+        sample_rate: 48000,
+        sample_format: RawSampleFormat::F32,
+        endian: RawEndian::Le,
+        iq_order: IqOrder::Iq,
+    };
+    let mut reader = RawIqReader::from_stream(Box::new(std::io::Cursor::new(samples)), params).unwrap();
+    assert_eq!(reader.total_samples(), None);
+
+    let mut buffer = vec![0.0f32; 8];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 4);
+    assert_eq!(&buffer[..4], &[0.5, -0.5, 0.25, -0.25]);
+}
+
+/// Write a short 440 Hz mono WAV as signed integer PCM of the given depth,
+/// writing through i32 so depths other than 16 bits are exercised
+fn write_int_tone_wav(name: &str, bits_per_sample: u16) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample,
+        sample_format: SampleFormat::Int,
+    };
+    let full_scale = ((1u64 << (bits_per_sample - 1)) - 1) as f32;
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5;
+        writer.write_sample((sample * full_scale) as i32).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_wav_reader_24_bit_pcm_normalized() {
+    let path = write_int_tone_wav("sgvr_audio_i24.wav", 24);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+    assert_eq!(reader.metadata().codec, "WAV (24-bit int)");
+
+    let mut buffer = vec![0.0f32; 8000];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 8000);
+    assert!(buffer.iter().all(|s| s.abs() <= 1.0));
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((peak - 0.5).abs() < 0.01, "peak {}", peak);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_wav_reader_8_bit_pcm_normalized() {
+    let path = write_int_tone_wav("sgvr_audio_i8.wav", 8);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+    assert_eq!(reader.metadata().codec, "WAV (8-bit int)");
+
+    let mut buffer = vec![0.0f32; 8000];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 8000);
+    // 8-bit quantization is coarse, so allow a wider tolerance; hound maps
+    // the stored unsigned bytes onto -128..127, so -128 / 127 can slightly
+    // exceed full scale on the negative side
+    assert!(buffer.iter().all(|s| s.abs() <= 128.0 / 127.0));
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((peak - 0.5).abs() < 0.05, "peak {}", peak);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_downmix_averages_all_channels() {
+    let path = std::env::temp_dir().join("sgvr_audio_downmix.wav");
+    let spec = hound::WavSpec {
+        channels: 3,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    // Frame i carries (3i, -6i, 9i), whose average is exactly 2i
+    for i in 0..100i16 {
+        writer.write_sample(3 * i).unwrap();
+        writer.write_sample(-6 * i).unwrap();
+        writer.write_sample(9 * i).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let mut reader = WavAudioReader::open(&path, None, true).unwrap();
+    assert_eq!(reader.total_samples(), Some(100));
+
+    let mut buffer = vec![0.0f32; 100];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 100);
+    for (i, &sample) in buffer.iter().enumerate() {
+        let expected = 2.0 * i as f32 / i16::MAX as f32;
+        assert!((sample - expected).abs() < 1e-6, "frame {}: {} vs {}", i, sample, expected);
+    }
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_downmix_conflicts_with_channel_selection() {
+    let path = write_tone_wav("sgvr_audio_downmix_conflict.wav", SampleFormat::Int, 16);
+    let err = match WavAudioReader::open(&path, Some(0), true) {
+        Ok(_) => panic!("downmix with channel selection should be rejected"),
+        Err(e) => e,
+    };
+    assert!(err.to_string().contains("downmix"));
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_resample_reader_halves_rate_and_length() {
+    let path = write_tone_wav("sgvr_audio_resample.wav", SampleFormat::Int, 16);
+    let mut inner = WavAudioReader::open(&path, None, false).unwrap();
+    let mut reader = ResampleReader::new(&mut inner, 4000, 1);
+
+    assert_eq!(reader.sample_rate(), 4000);
+    let expected = reader.total_samples().unwrap();
+    assert_eq!(expected, 4000);
+    assert!(reader.metadata().codec.contains("resampled from 8000 Hz"));
+
+    let mut buffer = vec![0.0f32; 8192];
+    let mut read = 0;
+    loop {
+        let n = reader.read(&mut buffer[read..]).unwrap();
+        if n == 0 {
+            break;
+        }
+        read += n;
+    }
+    assert_eq!(read, expected);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_raw_reader_decodes_all_endian_and_order_combinations() {
+    // One I/Q pair as i16: I = 16384 (0.5 full scale), Q = -16384
+    let i: i16 = 16384;
+    let q: i16 = -16384;
+    let open_with = |bytes: Vec<u8>, endian: RawEndian, iq_order: IqOrder| {
+        let params = RawInputParams {
+            sample_rate: 8000,
+            sample_format: RawSampleFormat::I16,
+            endian,
+            iq_order,
+        };
+        let mut reader =
+            RawIqReader::from_stream(Box::new(std::io::Cursor::new(bytes)), params).unwrap();
+        let mut buffer = vec![0.0f32; 2];
+        assert_eq!(reader.read(&mut buffer).unwrap(), 2);
+        buffer
+    };
+    let expected_i = i as f32 / i16::MAX as f32;
+    let expected_q = q as f32 / i16::MAX as f32;
+
+    // Little-endian, I then Q: bytes in stream order decode as written
+    let le_iq: Vec<u8> = [i, q].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(open_with(le_iq, RawEndian::Le, IqOrder::Iq), [expected_i, expected_q]);
+
+    // Big-endian bytes under the be flag decode to the same values
+    let be_iq: Vec<u8> = [i, q].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(open_with(be_iq, RawEndian::Be, IqOrder::Iq), [expected_i, expected_q]);
+
+    // Q-first streams come out swapped back into I-then-Q order
+    let le_qi: Vec<u8> = [q, i].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(open_with(le_qi, RawEndian::Le, IqOrder::Qi), [expected_i, expected_q]);
+
+    let be_qi: Vec<u8> = [q, i].iter().flat_map(|s| s.to_be_bytes()).collect();
+    assert_eq!(open_with(be_qi, RawEndian::Be, IqOrder::Qi), [expected_i, expected_q]);
+}
+
+#[test]
+fn test_raw_reader_qi_order_drops_trailing_lone_component() {
+    // Three f32 scalars: one full Q/I pair plus a dangling component
+    let bytes: Vec<u8> = [0.25f32, 0.75, 0.5].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let params = RawInputParams {
+        sample_rate: 8000,
+        sample_format: RawSampleFormat::F32,
+        endian: RawEndian::Le,
+        iq_order: IqOrder::Qi,
+    };
+    let mut reader =
+        RawIqReader::from_stream(Box::new(std::io::Cursor::new(bytes)), params).unwrap();
+    let mut buffer = vec![0.0f32; 4];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 2);
+    assert_eq!(&buffer[..2], &[0.75, 0.25]);
+}
+
+#[test]
+fn test_seek_time_converts_seconds_to_samples() {
+    let path = write_tone_wav("sgvr_audio_seek_time.wav", SampleFormat::Int, 16);
+    let mut reader = WavAudioReader::open(&path, None, false).unwrap();
+
+    // 0.5 s at 8 kHz is 4000 samples
+    assert_eq!(reader.seek_time(0.5).unwrap(), 4000);
+    let mut buffer = vec![0.0f32; 8000];
+    assert_eq!(reader.read(&mut buffer).unwrap(), 4000);
+    assert!(reader.seek_time(-1.0).is_err());
+
+    std::fs::remove_file(&path).ok();
+}
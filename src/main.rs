@@ -1,5 +1,6 @@
 // spec-vis/src/main.rs
 
+mod audio;
 mod scalc;
 mod srend;
 
@@ -12,26 +13,189 @@ use std::time::Instant;
 enum CliWindowType {
     Hann,
     Hamming,
+    Flattop,
+    Gaussian,
+    Rectangular,
+    Nuttall,
+}
+
+/// Input signal type
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliSignalType {
+    Real,
+    Iq,
+}
+
+/// Image orientation: which axis time runs along
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliOrientation {
+    TimeX,
+    TimeY,
+}
+
+/// Column reduction mode for horizontal downsampling
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliColumnReduce {
+    Max,
+    Mean,
+    Min,
+}
+
+/// dB normalization mode for rendering
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliNormalization {
+    Peak,
+    Percentile,
+}
+
+/// Frequency axis scale for rendering
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliFreqScale {
+    Linear,
+    Log,
+}
+
+/// dB scaling for the spectrum values
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliDbScale {
+    Amplitude,
+    Power,
+}
+
+/// Sample encoding of a raw input file
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliSampleFormat {
+    I16,
+    F32,
+}
+
+/// Byte order of a raw input file
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliEndian {
+    Le,
+    Be,
+}
+
+/// Component order of interleaved I/Q pairs in a raw input file
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliIqOrder {
+    Iq,
+    Qi,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliPrecision {
+    F32,
+    F64,
+}
+
+/// Color space the gradient is interpolated in
+#[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
+enum CliInterpSpace {
+    Hsl,
+    Rgb,
+    Lab,
 }
 
 /// Color scheme for spectrogram rendering
+///
+/// The aliases keep the legacy specv-era names (navy, gray, bloody)
+/// working by mapping them onto the real schemes.
 #[derive(Copy, Clone, Debug, ValueEnum, PartialEq)]
 enum CliColorScheme {
+    #[value(alias = "navy")]
     Oceanic,
+    #[value(alias = "gray")]
     Grayscale,
+    #[value(alias = "bloody")]
     Inferno,
     Viridis,
     Synthwave,
     Sunset,
+    Diverging,
+}
+
+/// Dynamic range argument: a fixed dB value or `auto`, which derives
+/// the display range from the estimated noise floor of the data
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum CliDynamicRange {
+    Auto,
+    Fixed(f32),
+}
+
+fn parse_dynamic_range(s: &str) -> Result<CliDynamicRange, String> {
+    if s.eq_ignore_ascii_case("auto") {
+        return Ok(CliDynamicRange::Auto);
+    }
+    let db = s.parse::<f32>()
+        .map_err(|_| format!("'{}' is neither a dB value nor 'auto'", s))?;
+    if db <= 0.0 || db.is_nan() {
+        return Err(format!("dynamic range must be greater than 0 dB (got {})", db));
+    }
+    Ok(CliDynamicRange::Fixed(db))
+}
+
+/// Tool subcommands; plain invocation without one renders spectrograms
+#[derive(clap::Subcommand, Debug)]
+enum CliCommand {
+    /// Generate a calibration WAV with tones, sweeps and noise
+    Gen(GenArgs),
+}
+
+/// Arguments of the `sgvr gen` test-signal generator
+#[derive(clap::Args, Debug)]
+struct GenArgs {
+    /// Output WAV path
+    #[arg(default_value = "test_signal.wav")]
+    output: String,
+
+    /// Sample rate, Hz
+    #[arg(long = "sample-rate", default_value_t = 44100)]
+    sample_rate: u32,
+
+    /// Signal duration, seconds
+    #[arg(long, default_value_t = 10.0)]
+    duration: f32,
+
+    /// Add a steady tone at this frequency, Hz (repeatable); a single
+    /// 1000 Hz tone is generated when nothing else is requested
+    #[arg(long = "tone")]
+    tones: Vec<f32>,
+
+    /// Add a linear sweep across the whole file, Hz
+    #[arg(long, value_name = "START:END")]
+    sweep: Option<String>,
+
+    /// Add white noise with this linear amplitude (0..1)
+    #[arg(long)]
+    noise: Option<f32>,
+
+    /// Peak amplitude of each tone/sweep component (0..1)
+    #[arg(long, default_value_t = 0.5)]
+    amplitude: f32,
 }
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+
     /// Window function type
     #[arg(short = 'w', long = "window-type", value_enum, default_value_t = CliWindowType::Hann)]
     window_type: CliWindowType,
 
+    /// Width of the Gaussian window as a fraction of the half-width
+    /// (only used with `-w gaussian`)
+    #[arg(long = "gaussian-sigma", default_value_t = scalc::DEFAULT_GAUSSIAN_SIGMA)]
+    gaussian_sigma: f32,
+
+    /// Round a non-power-of-two FFT size up to the next power of two
+    /// instead of warning about it
+    #[arg(long = "round-fft")]
+    round_fft: bool,
+
     /// Color scheme
     #[arg(short = 'c', long = "color-scheme", value_enum, default_value_t = CliColorScheme::Oceanic)]
     color_scheme: CliColorScheme,
@@ -40,24 +204,317 @@ struct Args {
     #[arg(short = 'i', long = "image-size", default_value = "2048x512")]
     image_size: String,
 
-    /// Save preview spectrogram
-    #[arg(short = 'p', long = "preview-save", default_value_t = true)]
+    /// Also save a small thumbnail (256 px wide) alongside the full image
+    #[arg(short = 'p', long = "preview-save", default_value_t = false)]
     preview_save: bool,
 
-    /// Input signal filename
-    file_name: String,
+    /// Input signal filename(s); each input produces one output image
+    #[arg(required_unless_present = "list_color_schemes")]
+    file_name: Vec<String>,
+
+    /// Print the available color schemes with their gradient stops and exit
+    #[arg(long = "list-color-schemes")]
+    list_color_schemes: bool,
+
+    /// Name of a rendering preset from the presets file; values from the
+    /// preset fill in any flag not given explicitly on the command line
+    #[arg(long = "preset")]
+    preset: Option<String>,
+
+    /// Path of the TOML presets file (default: ~/.config/sgvr/presets.toml)
+    #[arg(long = "presets-file", value_name = "PATH")]
+    presets_file: Option<String>,
+
+    /// Output image path (default: input filename with ".png" appended);
+    /// the format is chosen by the extension: png, jpg, bmp or tiff
+    #[arg(short = 'o', long = "output")]
+    output: Option<String>,
+
+    /// JPEG quality (1-100); ignored for other output formats
+    #[arg(long = "quality", default_value_t = 90)]
+    quality: u8,
+
+    /// Instead of one image, write a sequence of fixed-width PNG tiles into
+    /// this directory (frame_000000.png, ...) for stitching into a scrolling
+    /// video, e.g. with ffmpeg
+    #[arg(long = "frames")]
+    frames: Option<String>,
+
+    /// Seconds of signal covered by each tile written with --frames
+    #[arg(long = "frame-duration", default_value_t = 10.0)]
+    frame_duration: f32,
+
+    /// Suppress the progress bar and per-stage status output;
+    /// errors still go to stderr
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Print extra timing detail (frame count and average time per frame)
+    #[arg(long, conflicts_with = "quiet")]
+    verbose: bool,
+
+    /// Cap the rendering thread pool at this many threads
+    /// (0 or unset: one per core)
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Average all frames into a single spectrum (Welch's method),
+    /// for stationary-signal PSD and noise-floor measurements
+    #[arg(long)]
+    average: bool,
+
+    /// Subtract the estimated stationary noise floor (per-bin median over
+    /// time, smoothed across frequency) before rendering
+    #[arg(long)]
+    denoise: bool,
+
+    /// Collapse all frames into a single max-hold spectrum, where each bin
+    /// keeps the loudest dB it ever reached (preserves brief bursts)
+    #[arg(long = "max-hold", conflicts_with = "average")]
+    max_hold: bool,
+
+    /// Report the time-averaged dB at the bin nearest this frequency and
+    /// its SNR against the neighboring bins
+    #[arg(long = "measure", value_name = "HZ")]
+    measure: Option<f32>,
+
+    /// Render the positive frame-to-frame dB rise instead of raw dB,
+    /// emphasizing onsets and hiding steady tones
+    #[arg(long = "transient")]
+    transient: bool,
+
+    /// Write a histogram of the dB values (bin edges and counts) to this
+    /// CSV path, with suggested floor/peak percentiles on stdout
+    #[arg(long = "histogram", value_name = "PATH")]
+    histogram: Option<String>,
+
+    /// Caption text stamped in a bar above the spectrogram
+    #[arg(long = "title", value_name = "TEXT")]
+    title: Option<String>,
+
+    /// Mark this many of the strongest persistent tones with faint
+    /// horizontal lines (labeled with their frequency when --axes is on)
+    #[arg(long = "mark-peaks")]
+    mark_peaks: Option<usize>,
+
+    /// Absolute dB floor: pixels below it take the gradient's bottom color,
+    /// hiding sub-noise detail regardless of the dynamic range
+    #[arg(long = "floor-db")]
+    floor_db: Option<f32>,
+
+    /// Fit a line to the ridge of maximum energy, report the sweep's
+    /// start/end frequency and rate, and overlay the fit on the image
+    #[arg(long = "detect-chirp", default_value_t = false)]
+    detect_chirp: bool,
+
+    /// Paint saturated cells of clipped frames in this "#RRGGBB" color
+    #[arg(long = "clip-color", value_name = "HEX")]
+    clip_color: Option<String>,
+
+    /// Resynthesize the audio from the spectrogram (inverse STFT with
+    /// overlap-add) and write it to this WAV path
+    #[arg(long = "resynth", value_name = "PATH")]
+    resynth: Option<String>,
+
+    /// Render two stacked panels: magnitude on top, instantaneous frequency
+    /// (per-bin phase derivative) below, sharing the time axis
+    #[arg(long = "iq-dual", conflicts_with = "frames")]
+    iq_dual: bool,
+
+    /// Analyze I and Q each as a real signal and stack their spectrograms,
+    /// for spotting DC offset or channel imbalance (two-channel input)
+    #[arg(long = "iq-split", conflicts_with_all = ["iq_dual", "frames"])]
+    iq_split: bool,
 
     /// FFT size
     #[arg(short = 'f', long = "fft-size", default_value_t = 2048)]
     fft_size: usize,
 
-    /// Hop length
-    #[arg(long, default_value_t = 512)]
-    hop_length: usize,
+    /// Zero-padding factor: the FFT runs at `fft_size * factor` points while
+    /// the analysis window stays `fft_size`, interpolating the frequency axis
+    #[arg(long = "zero-pad", default_value_t = 1)]
+    zero_pad: usize,
+
+    /// Hop length (default: 512)
+    #[arg(long)]
+    hop_length: Option<usize>,
+
+    /// Window overlap in percent (0..100); derives the hop length from the FFT size
+    #[arg(long, conflicts_with = "hop_length")]
+    overlap: Option<f32>,
+
+    /// Floating-point precision the FFT runs at; f64 avoids accumulation
+    /// error in long FFTs at roughly twice the cost
+    #[arg(long, value_enum, default_value_t = CliPrecision::F32)]
+    precision: CliPrecision,
+
+    /// Dynamic range, dB, or 'auto' to derive it from the noise floor
+    #[arg(short = 'd', long = "dynamic-range", value_parser = parse_dynamic_range, default_value = "110")]
+    dynamic_range: CliDynamicRange,
+
+    /// Frequency axis scale: linear or logarithmic
+    #[arg(long = "freq-scale", value_enum, default_value_t = CliFreqScale::Linear)]
+    freq_scale: CliFreqScale,
+
+    /// Color space the gradient is interpolated in: hsl (the classic vivid
+    /// look), rgb (most predictable) or lab (perceptually even)
+    #[arg(long = "gradient-space", value_enum, default_value_t = CliInterpSpace::Hsl)]
+    gradient_space: CliInterpSpace,
+
+    /// Render low frequencies (bin 0) at the top of the image instead of the bottom
+    #[arg(long = "freq-top", default_value_t = false)]
+    freq_top: bool,
+
+    /// Lowest frequency shown in the image, Hz (out-of-range values clamp)
+    #[arg(long = "freq-min")]
+    freq_min: Option<f32>,
+
+    /// Highest frequency shown in the image, Hz (out-of-range values clamp)
+    #[arg(long = "freq-max")]
+    freq_max: Option<f32>,
+
+    /// Render a coarse preview image (small FFT, large hop) before the full pass
+    #[arg(long = "fast-preview", default_value_t = false)]
+    fast_preview: bool,
+
+    /// Fail on NaN/Inf or out-of-range samples instead of silently clamping them
+    #[arg(long = "strict", default_value_t = false)]
+    strict: bool,
+
+    /// Center frames on their hop position using librosa-style reflect padding
+    #[arg(long = "center", default_value_t = false)]
+    center: bool,
+
+    /// Subtract each frame's mean to suppress a DC bias in the recording
+    #[arg(long = "remove-dc", default_value_t = false)]
+    remove_dc: bool,
+
+    /// Magnitude floor for the dB conversion (default corresponds to -180 dB)
+    #[arg(long = "mag-floor", default_value_t = scalc::DEFAULT_MAG_FLOOR)]
+    mag_floor: f32,
+
+    /// Signal type: real audio, or interleaved I/Q
+    #[arg(long = "signal-type", value_enum, default_value_t = CliSignalType::Real)]
+    signal_type: CliSignalType,
+
+    /// Treat the input as a headerless raw file regardless of its extension
+    #[arg(long = "raw", default_value_t = false)]
+    raw: bool,
+
+    /// Sample rate of a raw input file, Hz (required for raw input)
+    #[arg(long = "sample-rate")]
+    sample_rate: Option<u32>,
+
+    /// Sample encoding of a raw input file
+    #[arg(long = "sample-format", value_enum, default_value_t = CliSampleFormat::F32)]
+    sample_format: CliSampleFormat,
 
-    /// Dynamic range, dB
-    #[arg(short = 'd', long = "dynamic-range", default_value_t = 110.0)]
-    dynamic_range: f32,
+    /// Byte order of a raw input file
+    #[arg(long = "endian", value_enum, default_value_t = CliEndian::Le)]
+    endian: CliEndian,
+
+    /// Order of the I and Q components within each raw I/Q pair
+    #[arg(long = "iq-order", value_enum, default_value_t = CliIqOrder::Iq)]
+    iq_order: CliIqOrder,
+
+    /// Analyze only this channel of a multichannel file (0-based)
+    #[arg(long = "channel")]
+    channel: Option<usize>,
+
+    /// Average all channels into one mono signal instead of picking one
+    #[arg(long = "downmix", conflicts_with = "channel")]
+    downmix: bool,
+
+    /// Resample the audio to this rate, Hz, before the FFT (linear
+    /// interpolation), so captures with different rates are comparable
+    #[arg(long = "resample")]
+    resample: Option<u32>,
+
+    /// Fast low-fidelity preview: decimate the input by this factor before
+    /// the FFT (`--preview` alone picks a factor from the file length)
+    #[arg(long = "preview", value_name = "FACTOR", num_args = 0..=1, require_equals = true)]
+    preview: Option<Option<usize>>,
+
+    /// Start of the analyzed time range, as seconds or a `[hh:]mm:ss` timecode
+    #[arg(long = "start", value_parser = parse_timecode)]
+    start: Option<f32>,
+
+    /// End of the analyzed time range, as seconds or a `[hh:]mm:ss` timecode
+    #[arg(long = "end", value_parser = parse_timecode)]
+    end: Option<f32>,
+
+    /// Aggregate the spectrum into this many mel bands per frame
+    #[arg(long = "mel")]
+    mel: Option<usize>,
+
+    /// Reference magnitude for the dB conversion (e.g. the full-scale magnitude for dBFS)
+    #[arg(long = "db-ref", default_value_t = 1.0)]
+    db_ref: f32,
+
+    /// dB scale: amplitude (20*log10) or window-energy-normalized power (10*log10)
+    #[arg(long = "db-scale", value_enum, default_value_t = CliDbScale::Amplitude)]
+    db_scale: CliDbScale,
+
+    /// Export per-frame spectral features (spectral rolloff) to a CSV file
+    #[arg(long = "export-features")]
+    export_features: Option<String>,
+
+    /// Export the raw dB matrix as .csv (one row per frame) or NumPy .npy;
+    /// a "<path>.meta" sidecar records sample rate, FFT size and hop length
+    #[arg(long = "export")]
+    export: Option<String>,
+
+    /// Orientation: time-x (default) or waterfall-style time-y
+    #[arg(long = "orientation", value_enum, default_value_t = CliOrientation::TimeX)]
+    orientation: CliOrientation,
+
+    /// Blend adjacent frequency bins bilinearly when upscaling
+    #[arg(long = "interpolate", default_value_t = false)]
+    interpolate: bool,
+
+    /// How master columns collapse into one pixel column: max, mean or min
+    #[arg(long = "reduce", value_enum, default_value_t = CliColumnReduce::Max)]
+    reduce: CliColumnReduce,
+
+    /// Gamma for the color mapping; values above 1.0 brighten weak detail
+    #[arg(long = "gamma", default_value_t = 1.0)]
+    gamma: f32,
+
+    /// dB normalization: raw peak or a percentile of all values
+    #[arg(long = "normalize", value_enum, default_value_t = CliNormalization::Peak)]
+    normalize: CliNormalization,
+
+    /// Percentile for --normalize percentile (0..100)
+    #[arg(long = "percentile", default_value_t = 99.0)]
+    percentile: f32,
+
+    /// Draw labeled frequency and time axes around the spectrogram
+    #[arg(long = "axes", default_value_t = false)]
+    axes: bool,
+
+    /// Reverse the colormap (dark-on-light output, e.g. for printing)
+    #[arg(long = "invert-colormap", default_value_t = false)]
+    invert_colormap: bool,
+
+    /// Custom gradient as comma-separated hex stops, e.g. "#01041B,#4da4d5,#ffffff"
+    #[arg(long = "gradient", conflicts_with = "color_scheme")]
+    gradient: Option<String>,
+
+    /// Map 0 to the gradient center, spreading +/- values symmetrically
+    #[arg(long = "diverging", default_value_t = false)]
+    diverging: bool,
+
+    /// Render the dB difference against another file (uses the diverging scheme)
+    #[arg(long = "diff")]
+    diff: Option<String>,
+
+    /// Cache spectrogram data in a binary file to skip recomputation
+    #[arg(long = "cache")]
+    cache: Option<String>,
+
+    /// Print file metadata and exit without computing a spectrogram
+    #[arg(long = "info", default_value_t = false)]
+    info: bool,
 }
 
 /// Convert CLI window type to internal window type
@@ -66,6 +523,120 @@ impl From<CliWindowType> for scalc::WindowType {
         match w {
             CliWindowType::Hann => scalc::WindowType::Hann,
             CliWindowType::Hamming => scalc::WindowType::Hamming,
+            CliWindowType::Flattop => scalc::WindowType::FlatTop,
+            CliWindowType::Gaussian => scalc::WindowType::Gaussian,
+            CliWindowType::Rectangular => scalc::WindowType::Rectangular,
+            CliWindowType::Nuttall => scalc::WindowType::Nuttall,
+        }
+    }
+}
+
+/// Convert CLI orientation to internal orientation
+impl From<CliOrientation> for srend::Orientation {
+    fn from(o: CliOrientation) -> Self {
+        match o {
+            CliOrientation::TimeX => srend::Orientation::TimeX,
+            CliOrientation::TimeY => srend::Orientation::TimeY,
+        }
+    }
+}
+
+/// Convert CLI column reduction to internal column reduction
+impl From<CliColumnReduce> for srend::ColumnReduce {
+    fn from(r: CliColumnReduce) -> Self {
+        match r {
+            CliColumnReduce::Max => srend::ColumnReduce::Max,
+            CliColumnReduce::Mean => srend::ColumnReduce::Mean,
+            CliColumnReduce::Min => srend::ColumnReduce::Min,
+        }
+    }
+}
+
+/// Convert CLI normalization mode to internal normalization mode
+impl From<CliNormalization> for srend::Normalization {
+    fn from(n: CliNormalization) -> Self {
+        match n {
+            CliNormalization::Peak => srend::Normalization::Peak,
+            CliNormalization::Percentile => srend::Normalization::Percentile,
+        }
+    }
+}
+
+/// Convert CLI frequency scale to internal frequency scale
+impl From<CliFreqScale> for srend::FreqScale {
+    fn from(s: CliFreqScale) -> Self {
+        match s {
+            CliFreqScale::Linear => srend::FreqScale::Linear,
+            CliFreqScale::Log => srend::FreqScale::Log,
+        }
+    }
+}
+
+/// Convert CLI signal type to internal signal type
+impl From<CliSignalType> for scalc::SignalType {
+    fn from(s: CliSignalType) -> Self {
+        match s {
+            CliSignalType::Real => scalc::SignalType::Real,
+            CliSignalType::Iq => scalc::SignalType::Iq,
+        }
+    }
+}
+
+/// Convert CLI sample format to internal raw sample format
+impl From<CliSampleFormat> for audio::RawSampleFormat {
+    fn from(f: CliSampleFormat) -> Self {
+        match f {
+            CliSampleFormat::I16 => audio::RawSampleFormat::I16,
+            CliSampleFormat::F32 => audio::RawSampleFormat::F32,
+        }
+    }
+}
+
+impl From<CliEndian> for audio::RawEndian {
+    fn from(e: CliEndian) -> Self {
+        match e {
+            CliEndian::Le => audio::RawEndian::Le,
+            CliEndian::Be => audio::RawEndian::Be,
+        }
+    }
+}
+
+impl From<CliIqOrder> for audio::IqOrder {
+    fn from(o: CliIqOrder) -> Self {
+        match o {
+            CliIqOrder::Iq => audio::IqOrder::Iq,
+            CliIqOrder::Qi => audio::IqOrder::Qi,
+        }
+    }
+}
+
+/// Convert CLI gradient interpolation space to internal space
+impl From<CliInterpSpace> for srend::InterpSpace {
+    fn from(s: CliInterpSpace) -> Self {
+        match s {
+            CliInterpSpace::Hsl => srend::InterpSpace::Hsl,
+            CliInterpSpace::Rgb => srend::InterpSpace::Rgb,
+            CliInterpSpace::Lab => srend::InterpSpace::Lab,
+        }
+    }
+}
+
+/// Convert CLI precision to internal precision
+impl From<CliPrecision> for scalc::Precision {
+    fn from(p: CliPrecision) -> Self {
+        match p {
+            CliPrecision::F32 => scalc::Precision::F32,
+            CliPrecision::F64 => scalc::Precision::F64,
+        }
+    }
+}
+
+/// Convert CLI dB scale to internal dB scale
+impl From<CliDbScale> for scalc::DbScale {
+    fn from(s: CliDbScale) -> Self {
+        match s {
+            CliDbScale::Amplitude => scalc::DbScale::Amplitude,
+            CliDbScale::Power => scalc::DbScale::Power,
         }
     }
 }
@@ -80,89 +651,1026 @@ impl From<CliColorScheme> for srend::ColorScheme {
             CliColorScheme::Sunset => srend::ColorScheme::Sunset,
             CliColorScheme::Synthwave => srend::ColorScheme::Synthwave,
             CliColorScheme::Viridis => srend::ColorScheme::Viridis,
+            CliColorScheme::Diverging => srend::ColorScheme::Diverging,
         }
     }
 }
 
 const DEFAULT_IMAGE_WIDTH: u32 = 2048;
 const DEFAULT_IMAGE_HEIGHT: u32 = 512;
+const DEFAULT_HOP_LENGTH: usize = 512;
 
-fn parse_image_size(s: &str) -> (u32, u32) {
+/// Derive the hop length from an explicit value or an overlap percentage
+fn derive_hop_length(fft_size: usize, hop_length: Option<usize>, overlap: Option<f32>) -> Result<usize, String> {
+    match overlap {
+        Some(percent) => {
+            if !(0.0..100.0).contains(&percent) {
+                return Err(format!("--overlap must be in the range 0..100 (got {})", percent));
+            }
+            Ok((fft_size as f32 * (1.0 - percent / 100.0)).round() as usize)
+        }
+        None => Ok(hop_length.unwrap_or(DEFAULT_HOP_LENGTH)),
+    }
+}
+
+/// Resolve the output image path: an explicit --output wins, otherwise the
+/// input filename with ".png" appended
+///
+/// A nonexistent output directory is reported here, before any computation,
+/// instead of surfacing as a raw save failure at the very end.
+fn resolve_output_path(file_name: &str, output: Option<&str>) -> Result<String, String> {
+    let path = match output {
+        Some(p) => p.to_string(),
+        // Stdin input has no file name to derive the default output from
+        None if file_name == "-" => {
+            return Err("reading from stdin requires -o/--output".to_string());
+        }
+        None => format!("{}.png", file_name),
+    };
+    let ext = std::path::Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if !matches!(ext.as_str(), "png" | "jpg" | "jpeg" | "bmp" | "tiff" | "tif") {
+        return Err(format!(
+            "unsupported output format '{}' (supported: png, jpg, bmp, tiff)", ext
+        ));
+    }
+    if let Some(dir) = std::path::Path::new(&path).parent()
+        && !dir.as_os_str().is_empty()
+        && !dir.is_dir()
+    {
+        return Err(format!("output directory '{}' does not exist", dir.display()));
+    }
+    Ok(path)
+}
+
+/// Save the rendered image, routing JPEG output through an encoder that
+/// honors --quality; all other formats are inferred from the extension
+fn save_image(image: &image::RgbImage, path: &str, quality: u8) -> Result<(), Box<dyn std::error::Error>> {
+    let is_jpeg = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg"));
+    if is_jpeg {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(file, quality);
+        image.write_with_encoder(encoder)?;
+    } else {
+        image.save(path)?;
+    }
+    Ok(())
+}
+
+/// Check that the requested time range is non-negative and properly ordered;
+/// clamping to the actual file duration happens during calculation
+/// Parse `ss[.fff]`, `mm:ss` or `hh:mm:ss` into seconds
+fn parse_timecode(s: &str) -> Result<f32, String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    if parts.len() > 3 {
+        return Err(format!("timecode '{}' has too many ':' separated fields (use [hh:]mm:ss)", s));
+    }
+    let mut seconds = 0.0f32;
+    for part in &parts {
+        let value: f32 = part.parse()
+            .map_err(|_| format!("invalid timecode component '{}' in '{}'", part, s))?;
+        if value < 0.0 {
+            return Err(format!("timecode '{}' must not contain negative components", s));
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(seconds)
+}
+
+/// Warning for FFT sizes rustfft handles but runs slower on; power-of-two
+/// sizes use the fastest code path
+fn fft_size_warning(fft_size: usize) -> Option<String> {
+    if fft_size == 0 || fft_size.is_power_of_two() {
+        return None;
+    }
+    Some(format!(
+        "Warning: FFT size {} is not a power of two; {} or {} would be faster (or pass --round-fft)",
+        fft_size,
+        fft_size.next_power_of_two() / 2,
+        fft_size.next_power_of_two()
+    ))
+}
+
+fn validate_time_range(start: Option<f32>, end: Option<f32>) -> Result<(), String> {
+    if let Some(s) = start
+        && s < 0.0
+    {
+        return Err(format!("--start must not be negative (got {})", s));
+    }
+    if let Some(e) = end
+        && e < 0.0
+    {
+        return Err(format!("--end must not be negative (got {})", e));
+    }
+    if let (Some(s), Some(e)) = (start, end)
+        && e <= s
+    {
+        return Err(format!("--end ({}) must be greater than --start ({})", e, s));
+    }
+    Ok(())
+}
+
+/// Parse the `--image-size` value, allowing either dimension to be omitted:
+/// "2048x" and "x512" leave the other side as `None`, to be derived from the
+/// calculated spectrogram shape. Malformed input falls back to the defaults.
+fn parse_image_size_spec(s: &str) -> (Option<u32>, Option<u32>) {
     let parts: Vec<&str> = s.split('x').collect();
     if parts.len() == 2 {
-        let w = parts[0].parse().unwrap_or(DEFAULT_IMAGE_WIDTH);
-        let h = parts[1].parse().unwrap_or(DEFAULT_IMAGE_HEIGHT);
-        // Return (w, h) only if both are non-zero, otherwise fall through to default
-        if w != 0 && h != 0 {
-            return (w, h);
+        let (w_str, h_str) = (parts[0], parts[1]);
+        match (w_str.is_empty(), h_str.is_empty()) {
+            (false, true) => {
+                if let Ok(w) = w_str.parse::<u32>()
+                    && w != 0
+                {
+                    return (Some(w), None);
+                }
+            }
+            (true, false) => {
+                if let Ok(h) = h_str.parse::<u32>()
+                    && h != 0
+                {
+                    return (None, Some(h));
+                }
+            }
+            (false, false) => {
+                // The full form keeps its legacy forgiveness: each side
+                // falls back to its default individually on a parse error
+                let w = w_str.parse().unwrap_or(DEFAULT_IMAGE_WIDTH);
+                let h = h_str.parse().unwrap_or(DEFAULT_IMAGE_HEIGHT);
+                if w != 0 && h != 0 {
+                    return (Some(w), Some(h));
+                }
+            }
+            (true, true) => {}
         }
     }
-    (DEFAULT_IMAGE_WIDTH, DEFAULT_IMAGE_HEIGHT)
+    (Some(DEFAULT_IMAGE_WIDTH), Some(DEFAULT_IMAGE_HEIGHT))
 }
 
-fn main() {
-    let args = Args::parse();
-
-    println!("Process file: '{}'", args.file_name);
-    let (width, height) = parse_image_size(&args.image_size);
-    println!("Generate {}x{}px spec image with color scheme '{:?}'", width, height, args.color_scheme);
-    println!(
-        "FFT size = {}, Hop length = {}, Window type = {:?}, Dynamic range = {} dB",
-        args.fft_size, args.hop_length, args.window_type, args.dynamic_range
+#[allow(dead_code)] // Legacy full-form helper, exercised by tests
+fn parse_image_size(s: &str) -> (u32, u32) {
+    let (w, h) = parse_image_size_spec(s);
+    (w.unwrap_or(DEFAULT_IMAGE_WIDTH), h.unwrap_or(DEFAULT_IMAGE_HEIGHT))
+}
+
+/// Parse one "#RRGGBB" value into a color
+fn parse_hex_color(part: &str) -> Result<srend::Color, String> {
+    let hex = part.strip_prefix('#')
+        .ok_or_else(|| format!("color '{}' must start with '#'", part))?;
+    if hex.len() != 6 {
+        return Err(format!("color '{}' must be in #RRGGBB form", part));
+    }
+    let rgb = u32::from_str_radix(hex, 16)
+        .map_err(|_| format!("color '{}' is not valid hex", part))?;
+    Ok(srend::Color::new_rgb(rgb))
+}
+
+/// Parse a comma-separated list of "#RRGGBB" stops into custom gradient colors
+fn parse_gradient(s: &str) -> Result<Vec<srend::Color>, String> {
+    let mut stops = Vec::new();
+    for part in s.split(',') {
+        stops.push(parse_hex_color(part.trim())?);
+    }
+    if stops.len() < 2 {
+        return Err(format!("--gradient needs at least two stops (got {})", stops.len()));
+    }
+    Ok(stops)
+}
+
+/// Width of one dB histogram bin written by `--histogram`
+const HISTOGRAM_BIN_DB: f32 = 1.0;
+
+/// Write the distribution of dB values as CSV (bin edges plus counts),
+/// for picking a dynamic range that fits the data
+fn export_histogram(spec_data: &scalc::SpectrogramData, path: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "bin_low_db,bin_high_db,count")?;
+
+    let values: Vec<f32> = spec_data.data.iter()
+        .flat_map(|col| col.iter())
+        .filter(|v| v.is_finite())
+        .cloned()
+        .collect();
+    if values.is_empty() {
+        return Ok(());
+    }
+    let min = values.iter().cloned().fold(f32::MAX, f32::min).floor();
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    let bins = (((max - min) / HISTOGRAM_BIN_DB).ceil() as usize).max(1);
+
+    let mut counts = vec![0usize; bins];
+    for &v in &values {
+        let idx = (((v - min) / HISTOGRAM_BIN_DB) as usize).min(bins - 1);
+        counts[idx] += 1;
+    }
+    for (i, count) in counts.iter().enumerate() {
+        let low = min + i as f32 * HISTOGRAM_BIN_DB;
+        writeln!(file, "{},{},{}", low, low + HISTOGRAM_BIN_DB, count)?;
+    }
+    Ok(())
+}
+
+/// Floor/peak percentiles suggested alongside the histogram, and the value
+/// of a sorted slice at a given percentile
+const HISTOGRAM_FLOOR_PERCENTILE: f32 = 1.0;
+const HISTOGRAM_PEAK_PERCENTILE: f32 = 99.0;
+
+fn percentile_of_sorted(sorted: &[f32], percentile: f32) -> f32 {
+    let idx = ((percentile.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+    sorted[idx]
+}
+
+/// Percentage of total energy used for the spectral rolloff feature
+const ROLLOFF_PERCENT: f32 = 0.85;
+
+/// Write per-frame spectral features to a CSV file
+fn export_features_csv(spec_data: &scalc::SpectrogramData, path: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "frame,rolloff_hz")?;
+    let rolloff = scalc::spectral_rolloff(spec_data, ROLLOFF_PERCENT);
+    for (i, value) in rolloff.iter().enumerate() {
+        writeln!(file, "{},{}", i, value)?;
+    }
+    Ok(())
+}
+
+/// Write the dB matrix as CSV, one row per time frame
+fn export_matrix_csv(spec_data: &scalc::SpectrogramData, path: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for col in &spec_data.data {
+        let row: Vec<String> = col.iter().map(|v| v.to_string()).collect();
+        writeln!(file, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Write the dB matrix as a NumPy .npy file (2D float32, C order)
+fn export_matrix_npy(spec_data: &scalc::SpectrogramData, path: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    let frames = spec_data.data.len();
+    let bins = spec_data.data.first().map_or(0, |col| col.len());
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        frames, bins
     );
-    println!();
+    // The npy format v1.0 pads the header with spaces so that the data
+    // section starts at a multiple of 64 bytes, terminated by a newline
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+    header.push('\n');
+
+    file.write_all(b"\x93NUMPY\x01\x00")?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+    for col in &spec_data.data {
+        for value in col {
+            file.write_all(&value.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Export the spectrogram matrix, dispatching on the output extension,
+/// and write the "<path>.meta" sidecar describing the calculation
+fn export_matrix(
+    spec_data: &scalc::SpectrogramData,
+    params: &scalc::CalcParams,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "csv" => export_matrix_csv(spec_data, path)?,
+        "npy" => export_matrix_npy(spec_data, path)?,
+        other => return Err(format!("unsupported export format '{}' (supported: csv, npy)", other).into()),
+    }
+
+    use std::io::Write;
+    let mut meta = std::fs::File::create(format!("{}.meta", path))?;
+    writeln!(meta, "sample_rate={}", spec_data.sample_rate)?;
+    writeln!(meta, "n_fft={}", params.n_fft)?;
+    writeln!(meta, "hop_length={}", params.hop_length)?;
+    Ok(())
+}
+
+/// Parse the command line and overlay the requested preset, if any
+///
+/// Presets only fill in flags the user did not set explicitly, so the
+/// command line always wins over the presets file.
+fn parse_args_from<I, T>(argv: I) -> Result<Args, Box<dyn std::error::Error>>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    use clap::{CommandFactory, FromArgMatches};
+
+    let matches = Args::command().get_matches_from(argv);
+    let mut args = Args::from_arg_matches(&matches)?;
+    if let Some(name) = args.preset.clone() {
+        apply_preset(&mut args, &matches, &name)?;
+    }
+    Ok(args)
+}
+
+/// Default location of the presets file
+fn default_presets_path() -> Option<std::path::PathBuf> {
+    std::env::var_os("HOME")
+        .map(|home| std::path::PathBuf::from(home).join(".config/sgvr/presets.toml"))
+}
+
+/// Overlay the named preset from the TOML presets file onto parsed args,
+/// skipping every flag the user passed explicitly on the command line
+///
+/// The file maps preset names to tables of CLI long option names:
+///
+/// ```toml
+/// [radio]
+/// fft-size = 4096
+/// color-scheme = "viridis"
+/// dynamic-range = "auto"
+/// ```
+fn apply_preset(
+    args: &mut Args,
+    matches: &clap::ArgMatches,
+    name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match &args.presets_file {
+        Some(path) => std::path::PathBuf::from(path),
+        None => default_presets_path().ok_or("cannot locate the presets file: HOME is not set")?,
+    };
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read presets file {}: {}", path.display(), e))?;
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e| format!("invalid presets file {}: {}", path.display(), e))?;
+    let preset = table
+        .get(name)
+        .and_then(|v| v.as_table())
+        .ok_or_else(|| format!("preset '{}' not found in {}", name, path.display()))?;
+
+    for (key, value) in preset {
+        // Preset keys use the CLI long names; clap ids use underscores
+        let id = key.replace('-', "_");
+        if !matches!(
+            key.as_str(),
+            "fft-size" | "hop-length" | "overlap" | "window-type" | "color-scheme"
+                | "gradient-space" | "freq-scale" | "db-scale" | "image-size"
+                | "dynamic-range" | "gamma" | "axes"
+        ) {
+            return Err(format!("unknown key '{}' in preset '{}'", key, name).into());
+        }
+        if matches.value_source(&id) == Some(clap::parser::ValueSource::CommandLine) {
+            continue;
+        }
+        match key.as_str() {
+            "fft-size" => args.fft_size = preset_usize(key, value)?,
+            "hop-length" => args.hop_length = Some(preset_usize(key, value)?),
+            "overlap" => args.overlap = Some(preset_f32(key, value)?),
+            "window-type" => args.window_type = preset_enum(key, value)?,
+            "color-scheme" => args.color_scheme = preset_enum(key, value)?,
+            "gradient-space" => args.gradient_space = preset_enum(key, value)?,
+            "freq-scale" => args.freq_scale = preset_enum(key, value)?,
+            "db-scale" => args.db_scale = preset_enum(key, value)?,
+            "image-size" => args.image_size = preset_string(key, value)?,
+            "dynamic-range" => {
+                let raw = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(i) => i.to_string(),
+                    toml::Value::Float(f) => f.to_string(),
+                    _ => return Err("preset key 'dynamic-range' must be a dB value or 'auto'".into()),
+                };
+                args.dynamic_range = parse_dynamic_range(&raw)
+                    .map_err(|e| format!("preset key 'dynamic-range': {}", e))?;
+            }
+            "gamma" => args.gamma = preset_f32(key, value)?,
+            "axes" => args.axes = preset_bool(key, value)?,
+            _ => unreachable!("key validated above"),
+        }
+    }
+    Ok(())
+}
+
+fn preset_usize(key: &str, value: &toml::Value) -> Result<usize, String> {
+    value
+        .as_integer()
+        .and_then(|v| usize::try_from(v).ok())
+        .ok_or_else(|| format!("preset key '{}' must be a non-negative integer", key))
+}
+
+fn preset_f32(key: &str, value: &toml::Value) -> Result<f32, String> {
+    match value {
+        toml::Value::Float(f) => Ok(*f as f32),
+        toml::Value::Integer(i) => Ok(*i as f32),
+        _ => Err(format!("preset key '{}' must be a number", key)),
+    }
+}
+
+fn preset_bool(key: &str, value: &toml::Value) -> Result<bool, String> {
+    value
+        .as_bool()
+        .ok_or_else(|| format!("preset key '{}' must be a boolean", key))
+}
+
+fn preset_string(key: &str, value: &toml::Value) -> Result<String, String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("preset key '{}' must be a string", key))
+}
+
+fn preset_enum<T: ValueEnum>(key: &str, value: &toml::Value) -> Result<T, String> {
+    let raw = preset_string(key, value)?;
+    T::from_str(&raw, true).map_err(|_| format!("invalid value '{}' for preset key '{}'", raw, key))
+}
+
+fn main() {
+    let mut args = match parse_args_from(std::env::args_os()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    if args.list_color_schemes {
+        use std::io::IsTerminal;
+        if let Err(e) = list_color_schemes(&mut std::io::stdout(), std::io::stdout().is_terminal()) {
+            eprintln!("Error: {}", e);
+        }
+        return;
+    }
+
+    if let Some(CliCommand::Gen(gen_args)) = &args.command {
+        match generate_wav(gen_args) {
+            Ok(()) => println!("Generated '{}' ({} s at {} Hz)", gen_args.output, gen_args.duration, gen_args.sample_rate),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+        return;
+    }
+
+    if args.round_fft && !args.fft_size.is_power_of_two() {
+        args.fft_size = args.fft_size.next_power_of_two();
+        if !args.quiet {
+            eprintln!("Note: --round-fft raised the FFT size to {}", args.fft_size);
+        }
+    }
+    if !args.quiet
+        && let Some(warning) = fft_size_warning(args.fft_size)
+    {
+        eprintln!("{}", warning);
+    }
+
+    let hop_length = match derive_hop_length(args.fft_size, args.hop_length, args.overlap) {
+        Ok(hop) => hop,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = validate_time_range(args.start, args.end) {
+        eprintln!("Error: {}", e);
+        return;
+    }
+
+    if !(1..=100).contains(&args.quality) {
+        eprintln!("Error: --quality must be in the range 1..=100 (got {})", args.quality);
+        return;
+    }
+
+    if args.gaussian_sigma <= 0.0 || args.gaussian_sigma.is_nan() {
+        eprintln!("Error: --gaussian-sigma must be greater than 0 (got {})", args.gaussian_sigma);
+        return;
+    }
+
+    if args.zero_pad < 1 {
+        eprintln!("Error: --zero-pad factor must be at least 1 (got {})", args.zero_pad);
+        return;
+    }
+
+    if args.frames.is_some() && args.frame_duration <= 0.0 {
+        eprintln!("Error: --frame-duration must be positive (got {})", args.frame_duration);
+        return;
+    }
 
-    println!("Calculating spectrogram data...");
-    let start_calc = Instant::now();
+    if args.output.is_some() && args.file_name.len() > 1 {
+        eprintln!("Error: --output cannot be combined with multiple input files");
+        return;
+    }
+
+    // A bounded rayon pool caps the render parallelism on shared machines;
+    // 0 keeps the default pool with one thread per core
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(args.threads).build() {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Error: cannot build a {}-thread pool: {}", args.threads, e);
+            return;
+        }
+    };
+
+    // One calculator (and so one FFT planner) shared across all inputs,
+    // so batch runs reuse the cached FFT plans; a failed file is reported
+    // and the remaining ones are still processed
+    let mut calculator = scalc::SpectrogramCalculator::new();
+    for file_name in &args.file_name {
+        // `install` needs a Send result, which `Box<dyn Error>` is not
+        let result = pool.install(|| {
+            process_file(file_name, &args, hop_length, &mut calculator, &mut std::io::stdout())
+                .map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("Error processing '{}': {}", file_name, e);
+        }
+    }
+}
+
+/// Write the calibration WAV described by the `sgvr gen` arguments:
+/// the sum of the requested tones, sweep and noise, as 16-bit mono
+fn generate_wav(gen_args: &GenArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use std::f32::consts::PI;
+
+    if gen_args.sample_rate == 0 {
+        return Err("--sample-rate must be greater than 0".into());
+    }
+    if gen_args.duration <= 0.0 {
+        return Err(format!("--duration must be positive (got {})", gen_args.duration).into());
+    }
+    let sweep = match &gen_args.sweep {
+        Some(raw) => {
+            let parsed = raw
+                .split_once(':')
+                .and_then(|(lo, hi)| Some((lo.trim().parse::<f32>().ok()?, hi.trim().parse::<f32>().ok()?)));
+            Some(parsed.ok_or_else(|| format!("invalid --sweep '{}', expected START:END in Hz", raw))?)
+        }
+        None => None,
+    };
+    let mut tones = gen_args.tones.clone();
+    if tones.is_empty() && sweep.is_none() && gen_args.noise.is_none() {
+        tones.push(1000.0);
+    }
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: gen_args.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&gen_args.output, spec)?;
+    let num_samples = (gen_args.duration as f64 * gen_args.sample_rate as f64).round() as u64;
+
+    // Small xorshift generator: plenty for test noise, no extra dependency
+    let mut rng_state: u32 = 0x2545_F491;
+    let mut next_noise = move || {
+        rng_state ^= rng_state << 13;
+        rng_state ^= rng_state >> 17;
+        rng_state ^= rng_state << 5;
+        (rng_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    };
+
+    for n in 0..num_samples {
+        let time = n as f32 / gen_args.sample_rate as f32;
+        let mut sample = 0.0f32;
+        for &hz in &tones {
+            sample += (2.0 * PI * hz * time).sin() * gen_args.amplitude;
+        }
+        if let Some((start, end)) = sweep {
+            // Linear chirp: the phase is the integral of the instantaneous
+            // frequency, which runs start..end over the file duration
+            let phase = 2.0 * PI * (start * time + (end - start) * time * time / (2.0 * gen_args.duration));
+            sample += phase.sin() * gen_args.amplitude;
+        }
+        if let Some(noise) = gen_args.noise {
+            sample += next_noise() * noise;
+        }
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Print each selectable color scheme with its hex stops; with `ansi` set
+/// (stdout is a terminal) a truecolor swatch precedes each stop
+fn list_color_schemes(out: &mut dyn std::io::Write, ansi: bool) -> std::io::Result<()> {
+    use srend::ColorScheme;
+    let schemes = [
+        ("oceanic", ColorScheme::Oceanic),
+        ("grayscale", ColorScheme::Grayscale),
+        ("inferno", ColorScheme::Inferno),
+        ("viridis", ColorScheme::Viridis),
+        ("synthwave", ColorScheme::Synthwave),
+        ("sunset", ColorScheme::Sunset),
+        ("diverging", ColorScheme::Diverging),
+    ];
+    for (name, scheme) in schemes {
+        write!(out, "{:<10}", name)?;
+        for c in srend::get_color_stops(&scheme) {
+            if ansi {
+                write!(out, " \x1b[48;2;{};{};{}m  \x1b[0m", c.r, c.g, c.b)?;
+            }
+            write!(out, " #{:02X}{:02X}{:02X}", c.r, c.g, c.b)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Width of the thumbnail written by `--preview-save`, px
+const THUMBNAIL_WIDTH: u32 = 256;
+
+/// Run the whole pipeline (read, calculate, render, save) for one input file
+fn process_file(
+    file_name: &str,
+    args: &Args,
+    hop_length: usize,
+    calculator: &mut scalc::SpectrogramCalculator,
+    out: &mut dyn std::io::Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    // In quiet mode all status output is swallowed; errors still reach stderr
+    let mut quiet_sink = std::io::sink();
+    let out: &mut dyn Write = if args.quiet { &mut quiet_sink } else { out };
+
+    let output_path = resolve_output_path(file_name, args.output.as_deref())?;
+
+    // Headerless raw input carries no metadata, so the sample rate must be
+    // given explicitly; recognized by extension or forced with --raw
+    let raw_ext = std::path::Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("raw") || e.eq_ignore_ascii_case("iqw"));
+    let raw_input = if args.raw || raw_ext {
+        match args.sample_rate {
+            Some(rate) => Some(audio::RawInputParams {
+                sample_rate: rate,
+                sample_format: args.sample_format.into(),
+                endian: args.endian.into(),
+                iq_order: args.iq_order.into(),
+            }),
+            None => return Err("raw input has no header: --sample-rate is required".into()),
+        }
+    } else {
+        None
+    };
+
+    if args.info {
+        let reader = audio::create_audio_reader(std::path::Path::new(file_name), args.channel, args.downmix, raw_input)?;
+        writeln!(out, "{}", reader.metadata().to_pretty_string())?;
+        return Ok(());
+    }
+
+    writeln!(out, "Process file: '{}'", file_name)?;
+    let (width_spec, height_spec) = parse_image_size_spec(&args.image_size);
+    let dim = |v: Option<u32>| v.map_or_else(|| "auto".to_string(), |v| v.to_string());
+    writeln!(out, "Generate {}x{}px spec image with color scheme '{:?}'",
+        dim(width_spec), dim(height_spec), args.color_scheme)?;
+    let range_desc = match args.dynamic_range {
+        CliDynamicRange::Fixed(db) => format!("{} dB", db),
+        CliDynamicRange::Auto => "auto".to_string(),
+    };
+    writeln!(out,
+        "FFT size = {}, Hop length = {}, Window type = {:?}, Dynamic range = {}",
+        args.fft_size, hop_length, args.window_type, range_desc
+    )?;
+    // Preview decimation: an explicit factor is taken as-is, a bare
+    // --preview derives one from the input length and the hop
+    let decimate = match args.preview {
+        None => None,
+        Some(Some(factor)) => Some(factor),
+        Some(None) => {
+            let reader = audio::create_audio_reader(
+                std::path::Path::new(file_name), args.channel, args.downmix, raw_input)?;
+            match reader.total_samples() {
+                Some(total) => Some(scalc::auto_decimation(total, hop_length)),
+                None => return Err(
+                    "auto preview factor needs a known input length; use --preview=<factor>".into()),
+            }
+        }
+    };
+    if let Some(factor) = decimate {
+        writeln!(out, "Preview mode: decimating input by {}x", factor)?;
+    }
+    writeln!(out)?;
 
     let params = scalc::CalcParams {
-        n_fft: args.fft_size,
-        hop_length: args.hop_length,
+        n_fft: args.fft_size * args.zero_pad,
+        hop_length,
         window_size: args.fft_size,
         window_type: args.window_type.into(),
+        gaussian_sigma: args.gaussian_sigma,
+        strict: args.strict,
+        mag_floor: args.mag_floor,
+        compute_phase: args.resynth.is_some() || args.iq_dual,
+        db_scale: args.db_scale.into(),
+        db_ref: args.db_ref,
+        mel_bands: args.mel,
+        channel: args.channel,
+        downmix: args.downmix,
+        resample: args.resample,
+        decimate,
+        start_time: args.start,
+        end_time: args.end,
+        raw_input,
+        signal_type: args.signal_type.into(),
+        center: args.center,
+        remove_dc: args.remove_dc,
+        precision: args.precision.into(),
     };
 
-    let pb = ProgressBar::new(1); // Length will be set in callback
-    pb.set_style(ProgressStyle::default_bar()
-        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)")
-        .unwrap()
-        .progress_chars("#>-"));
+    let mut render_params = srend::RenderParams {
+        width: width_spec.unwrap_or(DEFAULT_IMAGE_WIDTH),
+        height: height_spec.unwrap_or(DEFAULT_IMAGE_HEIGHT),
+        color_scheme: args.color_scheme.into(),
+        dynamic_range: match args.dynamic_range {
+            CliDynamicRange::Fixed(db) => db,
+            CliDynamicRange::Auto => srend::RenderParams::default().dynamic_range,
+        },
+        auto_range: args.dynamic_range == CliDynamicRange::Auto,
+        freq_top: args.freq_top,
+        freq_min: args.freq_min,
+        freq_max: args.freq_max,
+        freq_scale: args.freq_scale.into(),
+        invert_colormap: args.invert_colormap,
+        interp_space: args.gradient_space.into(),
+        orientation: args.orientation.into(),
+        reduce: args.reduce.into(),
+        interpolate: args.interpolate,
+        gamma: args.gamma,
+        normalize: args.normalize.into(),
+        percentile: args.percentile,
+        axes: args.axes,
+        hop_length,
+        diverging: args.diverging,
+        mark_peaks: args.mark_peaks,
+        floor_db: args.floor_db,
+        chirp_overlay: None,
+        clip_color: match &args.clip_color {
+            Some(hex) => Some(parse_hex_color(hex)?),
+            None => None,
+        },
+        title: args.title.clone(),
+    };
+
+    if let Some(gradient) = &args.gradient {
+        render_params.color_scheme = srend::ColorScheme::Custom(parse_gradient(gradient)?);
+    }
 
     use std::path::Path;
-    let spec_data_result = scalc::calculate_spectrogram(Path::new(&args.file_name), params, |processed, total| {
-        pb.set_length(total as u64);
-        pb.set_position(processed as u64);
-    });
 
-    pb.finish_with_message("Calculation completed");
+    if args.fast_preview {
+        writeln!(out, "Rendering fast preview...")?;
+        let start_preview = Instant::now();
+        match calculator.calculate(Path::new(file_name), scalc::preview_params(&params), |_, _| {}) {
+            Ok(preview_data) => {
+                let preview_image = srend::create_spectrogram_image(&preview_data, &render_params);
+                let preview_path = format!("{}.preview.png", file_name);
+                match preview_image.save(&preview_path) {
+                    Ok(_) => writeln!(out, "  Preview saved to {} in {:.2?}", preview_path, start_preview.elapsed())?,
+                    Err(e) => eprintln!("  Error saving preview image: {}", e),
+                }
+            }
+            Err(e) => eprintln!("  Error calculating preview: {}", e),
+        }
+        writeln!(out)?;
+    }
+
+    let cached_data = args.cache.as_ref()
+        .and_then(|cache_path| scalc::load_cache(Path::new(cache_path), &params));
 
-    let spec_data = match spec_data_result {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Error calculating spectrogram: {}", e);
-            return;
+    let mut spec_data = match cached_data {
+        Some(data) => {
+            writeln!(out, "Loaded spectrogram data from cache")?;
+            data
+        }
+        None => {
+            writeln!(out, "Calculating spectrogram data...")?;
+            let start_calc = Instant::now();
+
+            // Length will be set in the callback; quiet mode hides the bar
+            let pb = if args.quiet { ProgressBar::hidden() } else { ProgressBar::new(1) };
+            pb.set_style(ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%, ETA {eta})")
+                .unwrap()
+                .progress_chars("#>-"));
+
+            let spec_data_result = calculator.calculate(Path::new(file_name), params, |processed, total| {
+                pb.set_length(total as u64);
+                pb.set_position(processed as u64);
+            });
+
+            pb.finish_with_message("Calculation completed");
+
+            let data = spec_data_result?;
+            let elapsed = start_calc.elapsed();
+            writeln!(out, "  Completed in: {:.2?}", elapsed)?;
+            if args.verbose && !data.data.is_empty() {
+                writeln!(
+                    out,
+                    "  {} frames, {:.3} ms/frame average",
+                    data.data.len(),
+                    elapsed.as_secs_f64() * 1000.0 / data.data.len() as f64
+                )?;
+            }
+
+            if let Some(cache_path) = &args.cache {
+                match scalc::save_cache(&data, &params, Path::new(cache_path)) {
+                    Ok(_) => writeln!(out, "  Cache saved to {}", cache_path)?,
+                    Err(e) => eprintln!("  Warning: failed to save cache: {}", e),
+                }
+            }
+
+            data
         }
     };
-    println!("  Completed in: {:.2?}", start_calc.elapsed());
 
-    println!("\nCreating image...");
-    let start_view = Instant::now();
+    if let Some(resynth_path) = &args.resynth {
+        writeln!(out, "\nResynthesizing audio...")?;
+        let samples = scalc::resynthesize(&spec_data, &params)?;
+        let wav_spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: spec_data.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(resynth_path, wav_spec)?;
+        for s in &samples {
+            writer.write_sample((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()?;
+        writeln!(out, "  Audio saved to {}", resynth_path)?;
+    }
+
+    if let Some(other_file) = &args.diff {
+        writeln!(out, "\nCalculating difference against '{}'...", other_file)?;
+        let other_data = calculator.calculate(Path::new(other_file), params, |_, _| {})?;
+        spec_data = scalc::diff_spectrograms(&spec_data, &other_data);
+        // Difference data is signed, so switch to the zero-centered diverging render
+        render_params.color_scheme = srend::ColorScheme::Diverging;
+        render_params.diverging = true;
+    }
+
+    if args.average {
+        writeln!(out, "\nAveraging {} frames into a single spectrum...", spec_data.data.len())?;
+        spec_data = scalc::welch_average(&spec_data);
+    }
+
+    if args.max_hold {
+        writeln!(out, "\nCollapsing {} frames into a max-hold spectrum...", spec_data.data.len())?;
+        spec_data = scalc::max_hold(&spec_data);
+    }
 
-    let image = srend::create_spectrogram_image(&spec_data, width, height, args.color_scheme.into(), args.dynamic_range);
+    if args.denoise {
+        writeln!(out, "\nSubtracting the estimated noise floor...")?;
+        spec_data = scalc::spectral_denoise(&spec_data);
+    }
 
-    println!("  Completed in: {:.2?}", start_view.elapsed());
+    if args.transient {
+        writeln!(out, "\nTaking the positive temporal difference...")?;
+        spec_data = scalc::temporal_difference(&spec_data);
+    }
 
-    println!("\nSaving file...");
-    let output_path = format!("{}.png", args.file_name);
-    match image.save(&output_path) {
-        Ok(_) => println!(
-            "  Image successfully saved to {}",
-            output_path
-        ),
-        Err(e) => eprintln!("  Error saving image: {}", e),
+    if let Some(freq) = args.measure {
+        match scalc::measure_tone(&spec_data, freq) {
+            Some(m) => writeln!(
+                out,
+                "\nTone at {:.1} Hz (bin {}): {:.1} dB, SNR {:+.1} dB",
+                m.freq_hz, m.bin, m.level_db, m.snr_db
+            )?,
+            None => writeln!(out, "\nNo data to measure at {:.1} Hz", freq)?,
+        }
+    }
+
+    if args.detect_chirp {
+        match scalc::detect_chirp(&spec_data) {
+            Some(fit) => {
+                writeln!(
+                    out,
+                    "\nChirp: {:.1} Hz -> {:.1} Hz at {:+.1} Hz/s",
+                    fit.start_hz, fit.end_hz, fit.rate_hz_per_s
+                )?;
+                render_params.chirp_overlay = Some(fit);
+            }
+            None => writeln!(out, "\nNo clear chirp ridge detected")?,
+        }
+    }
+
+    if let Some(histogram_path) = &args.histogram {
+        writeln!(out, "\nExporting dB histogram...")?;
+        match export_histogram(&spec_data, histogram_path) {
+            Ok(_) => writeln!(out, "  Histogram saved to {}", histogram_path)?,
+            Err(e) => eprintln!("  Error exporting histogram: {}", e),
+        }
+        let mut values: Vec<f32> = spec_data.data.iter()
+            .flat_map(|col| col.iter())
+            .filter(|v| v.is_finite())
+            .cloned()
+            .collect();
+        if !values.is_empty() {
+            values.sort_unstable_by(f32::total_cmp);
+            let floor = percentile_of_sorted(&values, HISTOGRAM_FLOOR_PERCENTILE);
+            let peak = percentile_of_sorted(&values, HISTOGRAM_PEAK_PERCENTILE);
+            writeln!(out,
+                "  Suggested range: floor {:.1} dB (p{}), peak {:.1} dB (p{}) -> --dynamic-range {:.0}",
+                floor, HISTOGRAM_FLOOR_PERCENTILE, peak, HISTOGRAM_PEAK_PERCENTILE,
+                (peak - floor).max(1.0)
+            )?;
+        }
+    }
+
+    if let Some(csv_path) = &args.export_features {
+        writeln!(out, "\nExporting spectral features...")?;
+        match export_features_csv(&spec_data, csv_path) {
+            Ok(_) => writeln!(out, "  Features saved to {}", csv_path)?,
+            Err(e) => eprintln!("  Error exporting features: {}", e),
+        }
+    }
+
+    if let Some(export_path) = &args.export {
+        writeln!(out, "\nExporting spectrogram matrix...")?;
+        match export_matrix(&spec_data, &params, export_path) {
+            Ok(_) => writeln!(out, "  Matrix saved to {}", export_path)?,
+            Err(e) => eprintln!("  Error exporting matrix: {}", e),
+        }
+    }
+
+    // Dimensions omitted in --image-size follow the data shape: width
+    // from the frame count, height from the bin count
+    if width_spec.is_none() {
+        render_params.width = spec_data.data.len().max(1) as u32;
+    }
+    if height_spec.is_none() {
+        render_params.height = spec_data.data.first().map_or(1, |col| col.len().max(1)) as u32;
+    }
+
+    if let Some(frames_dir) = &args.frames {
+        writeln!(out, "\nWriting frame tiles...")?;
+        let start_view = Instant::now();
+        let count = srend::write_frame_tiles(
+            &spec_data, &render_params, Path::new(frames_dir), args.frame_duration)?;
+        writeln!(out, "  {} tiles saved to {} in {:.2?}", count, frames_dir, start_view.elapsed())?;
+    } else {
+        writeln!(out, "\nCreating image...")?;
+        let start_view = Instant::now();
+
+        let image = if args.iq_split {
+            writeln!(out, "  Rendering separate I and Q panels...")?;
+            let i_params = scalc::CalcParams {
+                channel: Some(0),
+                signal_type: scalc::SignalType::Real,
+                ..params
+            };
+            let q_params = scalc::CalcParams { channel: Some(1), ..i_params };
+            let i_data = calculator.calculate(Path::new(file_name), i_params, |_, _| {})?;
+            let q_data = calculator.calculate(Path::new(file_name), q_params, |_, _| {})?;
+            srend::render_iq_split(&i_data, &q_data, &render_params)
+        } else if args.iq_dual {
+            let inst = scalc::instantaneous_frequency(&spec_data).ok_or(
+                "--iq-dual needs at least two phase-enabled frames; \
+                 it cannot follow transforms that drop phase (e.g. --average)")?;
+            srend::render_iq_dual(&spec_data, &inst, &render_params)
+        } else {
+            srend::create_spectrogram_image(&spec_data, &render_params)
+        };
+
+        writeln!(out, "  Completed in: {:.2?}", start_view.elapsed())?;
+
+        writeln!(out, "\nSaving file...")?;
+        save_image(&image, &output_path, args.quality)?;
+        writeln!(out, "  Image successfully saved to {}", output_path)?;
+
+        if args.preview_save {
+            // Same data and render settings at thumbnail size, so the
+            // normalization matches the full image exactly
+            let thumb_params = srend::RenderParams {
+                width: THUMBNAIL_WIDTH,
+                height: (render_params.height * THUMBNAIL_WIDTH / render_params.width.max(1)).max(1),
+                axes: false,
+                title: None,
+                ..render_params.clone()
+            };
+            let thumb = srend::create_spectrogram_image(&spec_data, &thumb_params);
+            let thumb_path = std::path::Path::new(&output_path).with_extension("thumb.png");
+            thumb.save(&thumb_path)?;
+            writeln!(out, "  Thumbnail saved to {}", thumb_path.display())?;
+        }
     }
 
-    println!("\nCompleted.");
+    writeln!(out, "\nCompleted.")?;
+    Ok(())
 }
 
 #[cfg(test)]
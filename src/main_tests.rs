@@ -78,10 +78,217 @@ fn test_parse_image_size_large_numbers() {
     assert_eq!(h, 2048);
 }
 
+#[test]
+fn test_derive_hop_length_from_overlap() {
+    assert_eq!(derive_hop_length(2048, None, Some(75.0)), Ok(512));
+    assert_eq!(derive_hop_length(2048, None, Some(0.0)), Ok(2048));
+    assert_eq!(derive_hop_length(1024, None, Some(50.0)), Ok(512));
+}
+
+#[test]
+fn test_derive_hop_length_explicit_and_default() {
+    assert_eq!(derive_hop_length(2048, Some(256), None), Ok(256));
+    assert_eq!(derive_hop_length(2048, None, None), Ok(DEFAULT_HOP_LENGTH));
+}
+
+#[test]
+fn test_derive_hop_length_rejects_invalid_overlap() {
+    assert!(derive_hop_length(2048, None, Some(100.0)).is_err());
+    assert!(derive_hop_length(2048, None, Some(-5.0)).is_err());
+    assert!(derive_hop_length(2048, None, Some(150.0)).is_err());
+}
+
+#[test]
+fn test_resolve_output_path_default_appends_png() {
+    assert_eq!(resolve_output_path("input.wav", None), Ok("input.wav.png".to_string()));
+}
+
+#[test]
+fn test_resolve_output_path_explicit_override() {
+    let dir = std::env::temp_dir();
+    let path = dir.join("sgvr_out.png");
+    let path_str = path.to_str().unwrap();
+    assert_eq!(resolve_output_path("input.wav", Some(path_str)), Ok(path_str.to_string()));
+    // A bare filename has no directory component to check
+    assert_eq!(resolve_output_path("input.wav", Some("out.png")), Ok("out.png".to_string()));
+}
+
+#[test]
+fn test_resolve_output_path_rejects_missing_directory() {
+    let err = resolve_output_path("input.wav", Some("/nonexistent-sgvr-dir/out.png")).unwrap_err();
+    assert!(err.contains("does not exist"));
+}
+
+#[test]
+fn test_resolve_output_path_validates_extension() {
+    assert!(resolve_output_path("input.wav", Some("out.jpg")).is_ok());
+    assert!(resolve_output_path("input.wav", Some("out.bmp")).is_ok());
+    assert!(resolve_output_path("input.wav", Some("out.tiff")).is_ok());
+    let err = resolve_output_path("input.wav", Some("out.webm")).unwrap_err();
+    assert!(err.contains("unsupported output format"));
+    assert!(resolve_output_path("input.wav", Some("out")).is_err());
+}
+
+#[test]
+fn test_save_image_writes_jpeg_header() {
+    let img = image::RgbImage::new(8, 8);
+    let path = std::env::temp_dir().join("sgvr_test_quality.jpg");
+    save_image(&img, path.to_str().unwrap(), 90).unwrap();
+
+    // JPEG files start with the SOI marker FF D8
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(&bytes[..2], &[0xFF, 0xD8]);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_export_matrix_csv_roundtrip() {
+    let spec_data = scalc::SpectrogramData {
+        data: vec![vec![-1.5, 2.25, -3.0], vec![0.125, -40.75, 6.5]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: scalc::SignalType::Real,
+        hop_length: 512,
+    };
+    let path = std::env::temp_dir().join("sgvr_export.csv");
+    let params = scalc::CalcParams { n_fft: 1024, hop_length: 256, ..Default::default() };
+    export_matrix(&spec_data, &params, path.to_str().unwrap()).unwrap();
+
+    // Rust's shortest float formatting round-trips exactly through parsing
+    let parsed: Vec<Vec<f32>> = std::fs::read_to_string(&path).unwrap()
+        .lines()
+        .map(|line| line.split(',').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(parsed, spec_data.data);
+
+    let meta = std::fs::read_to_string(format!("{}.meta", path.display())).unwrap();
+    assert_eq!(meta, "sample_rate=8000\nn_fft=1024\nhop_length=256\n");
+
+    std::fs::remove_file(&path).ok();
+    std::fs::remove_file(format!("{}.meta", path.display())).ok();
+}
+
+#[test]
+fn test_export_matrix_npy_layout() {
+    let spec_data = scalc::SpectrogramData {
+        data: vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: scalc::SignalType::Real,
+        hop_length: 512,
+    };
+    let path = std::env::temp_dir().join("sgvr_export.npy");
+    let params = scalc::CalcParams::default();
+    export_matrix(&spec_data, &params, path.to_str().unwrap()).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let header = std::str::from_utf8(&bytes[10..10 + header_len]).unwrap();
+    assert!(header.contains("'shape': (3, 2)"));
+    assert!((10 + header_len).is_multiple_of(64));
+    // 6 f32 values follow the header in C order
+    assert_eq!(bytes.len(), 10 + header_len + 6 * 4);
+    assert_eq!(f32::from_le_bytes(bytes[10 + header_len..][..4].try_into().unwrap()), 1.0);
+
+    std::fs::remove_file(&path).ok();
+    std::fs::remove_file(format!("{}.meta", path.display())).ok();
+}
+
+#[test]
+fn test_export_matrix_rejects_unknown_extension() {
+    let spec_data = scalc::SpectrogramData { data: vec![vec![0.0]], sample_rate: 8000, phase: None, clipped: Vec::new(), signal_type: scalc::SignalType::Real, hop_length: 512 };
+    let err = export_matrix(&spec_data, &scalc::CalcParams::default(), "out.mat").unwrap_err();
+    assert!(err.to_string().contains("unsupported export format"));
+}
+
+/// Write a short test WAV for batch-processing tests
+fn write_batch_wav(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_batch_processing_two_files_two_images() {
+    let first = write_batch_wav("sgvr_batch_a.wav");
+    let second = write_batch_wav("sgvr_batch_b.wav");
+
+    let args = Args::parse_from([
+        "sgvr",
+        "-i", "64x32",
+        first.to_str().unwrap(),
+        second.to_str().unwrap(),
+    ]);
+    let mut calculator = scalc::SpectrogramCalculator::new();
+    for file_name in &args.file_name {
+        process_file(file_name, &args, 512, &mut calculator, &mut std::io::sink()).unwrap();
+    }
+
+    for input in [&first, &second] {
+        let output = format!("{}.png", input.display());
+        let size = std::fs::metadata(&output).unwrap().len();
+        assert!(size > 0, "{} should be a non-empty image", output);
+        std::fs::remove_file(&output).ok();
+    }
+
+    std::fs::remove_file(&first).ok();
+    std::fs::remove_file(&second).ok();
+}
+
+#[test]
+fn test_batch_error_does_not_abort_other_files() {
+    let good = write_batch_wav("sgvr_batch_good.wav");
+    let missing = std::env::temp_dir().join("sgvr_batch_missing.wav");
+
+    let args = Args::parse_from([
+        "sgvr",
+        "-i", "64x32",
+        missing.to_str().unwrap(),
+        good.to_str().unwrap(),
+    ]);
+    let mut calculator = scalc::SpectrogramCalculator::new();
+    let results: Vec<_> = args.file_name.iter()
+        .map(|f| process_file(f, &args, 512, &mut calculator, &mut std::io::sink()))
+        .collect();
+
+    assert!(results[0].is_err());
+    assert!(results[1].is_ok());
+
+    std::fs::remove_file(format!("{}.png", good.display())).ok();
+    std::fs::remove_file(&good).ok();
+}
+
+#[test]
+fn test_validate_time_range() {
+    assert!(validate_time_range(None, None).is_ok());
+    assert!(validate_time_range(Some(1.0), Some(2.0)).is_ok());
+    assert!(validate_time_range(Some(-1.0), None).is_err());
+    assert!(validate_time_range(None, Some(-1.0)).is_err());
+    assert!(validate_time_range(Some(2.0), Some(2.0)).is_err());
+    assert!(validate_time_range(Some(3.0), Some(2.0)).is_err());
+}
+
 #[test]
 fn test_cli_window_type_conversion() {
     assert_eq!(scalc::WindowType::Hann, CliWindowType::Hann.into());
     assert_eq!(scalc::WindowType::Hamming, CliWindowType::Hamming.into());
+    assert_eq!(scalc::WindowType::FlatTop, CliWindowType::Flattop.into());
 }
 
 #[test]
@@ -92,6 +299,17 @@ fn test_cli_color_scheme_conversion() {
     assert_eq!(srend::ColorScheme::Viridis, CliColorScheme::Viridis.into());
     assert_eq!(srend::ColorScheme::Synthwave, CliColorScheme::Synthwave.into());
     assert_eq!(srend::ColorScheme::Sunset, CliColorScheme::Sunset.into());
+    assert_eq!(srend::ColorScheme::Diverging, CliColorScheme::Diverging.into());
+}
+
+#[test]
+fn test_legacy_color_scheme_names_map_to_real_schemes() {
+    let scheme = |name: &str| {
+        Args::parse_from(["sgvr", "-c", name, "input.wav"]).color_scheme
+    };
+    assert_eq!(scheme("navy"), CliColorScheme::Oceanic);
+    assert_eq!(scheme("gray"), CliColorScheme::Grayscale);
+    assert_eq!(scheme("bloody"), CliColorScheme::Inferno);
 }
 
 #[test]
@@ -115,4 +333,343 @@ fn test_cli_enum_equality() {
     
     assert_eq!(CliColorScheme::Oceanic, CliColorScheme::Oceanic);
     assert_ne!(CliColorScheme::Oceanic, CliColorScheme::Grayscale);
-}
\ No newline at end of file
+}
+#[test]
+fn test_cli_db_scale_conversion() {
+    assert_eq!(scalc::DbScale::Amplitude, CliDbScale::Amplitude.into());
+    assert_eq!(scalc::DbScale::Power, CliDbScale::Power.into());
+}
+
+#[test]
+fn test_cli_signal_type_conversion() {
+    assert_eq!(scalc::SignalType::Real, CliSignalType::Real.into());
+    assert_eq!(scalc::SignalType::Iq, CliSignalType::Iq.into());
+}
+
+#[test]
+fn test_cli_sample_format_conversion() {
+    assert_eq!(audio::RawSampleFormat::I16, CliSampleFormat::I16.into());
+    assert_eq!(audio::RawSampleFormat::F32, CliSampleFormat::F32.into());
+}
+
+#[test]
+fn test_cli_freq_scale_conversion() {
+    assert_eq!(srend::FreqScale::Linear, CliFreqScale::Linear.into());
+    assert_eq!(srend::FreqScale::Log, CliFreqScale::Log.into());
+}
+
+#[test]
+fn test_parse_gradient_valid() {
+    let stops = parse_gradient("#01041B,#4da4d5,#ffffff").unwrap();
+    assert_eq!(stops.len(), 3);
+    assert_eq!(stops[0], srend::Color::new_rgb(0x01041B));
+    assert_eq!(stops[2], srend::Color::new_rgb(0xffffff));
+}
+
+#[test]
+fn test_parse_gradient_rejects_malformed_hex() {
+    assert!(parse_gradient("#01041B,#zzzzzz").is_err());
+    assert!(parse_gradient("#01041B,4da4d5").is_err());
+    assert!(parse_gradient("#01041B,#4da4").is_err());
+}
+
+#[test]
+fn test_parse_gradient_rejects_single_stop() {
+    assert!(parse_gradient("#01041B").is_err());
+}
+
+#[test]
+fn test_cli_normalization_conversion() {
+    assert_eq!(srend::Normalization::Peak, CliNormalization::Peak.into());
+    assert_eq!(srend::Normalization::Percentile, CliNormalization::Percentile.into());
+}
+
+#[test]
+fn test_cli_column_reduce_conversion() {
+    assert_eq!(srend::ColumnReduce::Max, CliColumnReduce::Max.into());
+    assert_eq!(srend::ColumnReduce::Mean, CliColumnReduce::Mean.into());
+    assert_eq!(srend::ColumnReduce::Min, CliColumnReduce::Min.into());
+}
+
+#[test]
+fn test_cli_orientation_conversion() {
+    assert_eq!(srend::Orientation::TimeX, CliOrientation::TimeX.into());
+    assert_eq!(srend::Orientation::TimeY, CliOrientation::TimeY.into());
+}
+
+#[test]
+fn test_parse_dynamic_range_accepts_auto_and_numbers() {
+    assert_eq!(parse_dynamic_range("auto"), Ok(CliDynamicRange::Auto));
+    assert_eq!(parse_dynamic_range("AUTO"), Ok(CliDynamicRange::Auto));
+    assert_eq!(parse_dynamic_range("80"), Ok(CliDynamicRange::Fixed(80.0)));
+    assert_eq!(parse_dynamic_range("72.5"), Ok(CliDynamicRange::Fixed(72.5)));
+    assert!(parse_dynamic_range("loud").is_err());
+}
+
+#[test]
+fn test_zero_pad_flag_scales_fft_size() {
+    let args = Args::parse_from(["sgvr", "--zero-pad", "2", "input.wav"]);
+    assert_eq!(args.zero_pad, 2);
+    assert_eq!(args.fft_size * args.zero_pad, 4096);
+
+    // Default factor of 1 keeps n_fft equal to the window size
+    let args = Args::parse_from(["sgvr", "input.wav"]);
+    assert_eq!(args.zero_pad, 1);
+}
+
+#[test]
+fn test_quiet_mode_writes_no_status_output() {
+    let input = write_batch_wav("sgvr_quiet.wav");
+    let output = std::env::temp_dir().join("sgvr_quiet.png");
+    let input_str = input.to_str().unwrap().to_string();
+    let mut calculator = scalc::SpectrogramCalculator::new();
+
+    let args = Args::parse_from([
+        "sgvr", "-q", "-f", "1024", "-o", output.to_str().unwrap(), &input_str,
+    ]);
+    let mut out = Vec::new();
+    process_file(&input_str, &args, 512, &mut calculator, &mut out).unwrap();
+    assert!(out.is_empty(), "quiet run wrote {} bytes of status output", out.len());
+
+    // The same run without -q produces the usual status lines
+    let args = Args::parse_from([
+        "sgvr", "-f", "1024", "-o", output.to_str().unwrap(), &input_str,
+    ]);
+    let mut out = Vec::new();
+    process_file(&input_str, &args, 512, &mut calculator, &mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("Process file"));
+
+    std::fs::remove_file(&input).ok();
+    std::fs::remove_file(&output).ok();
+}
+
+#[test]
+fn test_preview_save_writes_thumbnail_next_to_image() {
+    let input = write_batch_wav("sgvr_thumb.wav");
+    let output = std::env::temp_dir().join("sgvr_thumb.png");
+    let thumb = std::env::temp_dir().join("sgvr_thumb.thumb.png");
+    let input_str = input.to_str().unwrap().to_string();
+    let mut calculator = scalc::SpectrogramCalculator::new();
+
+    let args = Args::parse_from([
+        "sgvr", "-p", "-f", "1024", "-o", output.to_str().unwrap(), &input_str,
+    ]);
+    process_file(&input_str, &args, 512, &mut calculator, &mut std::io::sink()).unwrap();
+    assert!(output.exists(), "full image missing");
+    assert!(thumb.exists(), "thumbnail missing");
+    let img = image::open(&thumb).unwrap();
+    assert_eq!(img.width(), THUMBNAIL_WIDTH);
+
+    // Without the flag no thumbnail is written
+    std::fs::remove_file(&thumb).ok();
+    let args = Args::parse_from([
+        "sgvr", "-f", "1024", "-o", output.to_str().unwrap(), &input_str,
+    ]);
+    process_file(&input_str, &args, 512, &mut calculator, &mut std::io::sink()).unwrap();
+    assert!(!thumb.exists());
+
+    std::fs::remove_file(&input).ok();
+    std::fs::remove_file(&output).ok();
+}
+
+#[test]
+fn test_preset_populates_args_and_cli_overrides() {
+    let presets = std::env::temp_dir().join("sgvr_presets.toml");
+    std::fs::write(
+        &presets,
+        r#"
+[radio]
+fft-size = 4096
+window-type = "hamming"
+color-scheme = "viridis"
+dynamic-range = "auto"
+gamma = 1.5
+axes = true
+"#,
+    )
+    .unwrap();
+    let presets_str = presets.to_str().unwrap().to_string();
+
+    let args = parse_args_from([
+        "sgvr", "--preset", "radio", "--presets-file", &presets_str, "input.wav",
+    ])
+    .unwrap();
+    assert_eq!(args.fft_size, 4096);
+    assert_eq!(args.window_type, CliWindowType::Hamming);
+    assert_eq!(args.color_scheme, CliColorScheme::Viridis);
+    assert_eq!(args.dynamic_range, CliDynamicRange::Auto);
+    assert_eq!(args.gamma, 1.5);
+    assert!(args.axes);
+
+    // An explicit flag beats the preset value
+    let args = parse_args_from([
+        "sgvr", "--preset", "radio", "--presets-file", &presets_str,
+        "-f", "1024", "input.wav",
+    ])
+    .unwrap();
+    assert_eq!(args.fft_size, 1024);
+    assert_eq!(args.color_scheme, CliColorScheme::Viridis);
+
+    // Unknown presets and keys are reported by name
+    let err = parse_args_from([
+        "sgvr", "--preset", "missing", "--presets-file", &presets_str, "input.wav",
+    ])
+    .unwrap_err();
+    assert!(err.to_string().contains("preset 'missing' not found"));
+
+    std::fs::remove_file(&presets).ok();
+}
+
+#[test]
+fn test_gen_subcommand_writes_wav_with_requested_duration_and_rate() {
+    let path = std::env::temp_dir().join("sgvr_gen_tone.wav");
+    let args = parse_args_from([
+        "sgvr",
+        "gen",
+        path.to_str().unwrap(),
+        "--sample-rate",
+        "8000",
+        "--duration",
+        "0.5",
+        "--tone",
+        "440",
+    ])
+    .unwrap();
+    let Some(CliCommand::Gen(gen_args)) = &args.command else {
+        panic!("'gen' did not parse as a subcommand");
+    };
+    generate_wav(gen_args).unwrap();
+
+    let reader = hound::WavReader::open(&path).unwrap();
+    assert_eq!(reader.spec().sample_rate, 8000);
+    assert_eq!(reader.spec().channels, 1);
+    assert_eq!(reader.duration(), 4000); // 0.5 s at 8 kHz
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_parse_image_size_spec_partial_forms() {
+    // One side omitted: the other is kept, the missing one derives later
+    assert_eq!(parse_image_size_spec("2048x"), (Some(2048), None));
+    assert_eq!(parse_image_size_spec("x512"), (None, Some(512)));
+    // The full form still works, malformed input falls back to the defaults
+    assert_eq!(parse_image_size_spec("1024x768"), (Some(1024), Some(768)));
+    assert_eq!(
+        parse_image_size_spec("bogus"),
+        (Some(DEFAULT_IMAGE_WIDTH), Some(DEFAULT_IMAGE_HEIGHT))
+    );
+    assert_eq!(
+        parse_image_size_spec("x"),
+        (Some(DEFAULT_IMAGE_WIDTH), Some(DEFAULT_IMAGE_HEIGHT))
+    );
+}
+
+#[test]
+fn test_image_size_auto_height_follows_bin_count() {
+    let input = write_batch_wav("sgvr_auto_height.wav");
+    let output = std::env::temp_dir().join("sgvr_auto_height.png");
+    let mut args = parse_args_from([
+        "sgvr",
+        "-f",
+        "256",
+        "-i",
+        "64x",
+        "-o",
+        output.to_str().unwrap(),
+        input.to_str().unwrap(),
+    ])
+    .unwrap();
+    args.quiet = true;
+    let hop = derive_hop_length(args.fft_size, args.hop_length, args.overlap).unwrap();
+    let mut calculator = scalc::SpectrogramCalculator::new();
+    process_file(input.to_str().unwrap(), &args, hop, &mut calculator, &mut std::io::sink()).unwrap();
+
+    let img = image::open(&output).unwrap();
+    // Height derives from the bin count: 256-point FFT of real input -> 129
+    assert_eq!((img.width(), img.height()), (64, 129));
+    std::fs::remove_file(&input).ok();
+    std::fs::remove_file(&output).ok();
+}
+
+#[test]
+fn test_parse_image_size_zero_dimensions_yield_defaults() {
+    // A zero dimension must never propagate into a zero-size image
+    assert_eq!(parse_image_size("0x0"), (DEFAULT_IMAGE_WIDTH, DEFAULT_IMAGE_HEIGHT));
+    assert_eq!(parse_image_size("0x512"), (DEFAULT_IMAGE_WIDTH, DEFAULT_IMAGE_HEIGHT));
+    assert_eq!(parse_image_size_spec("0x"), (Some(DEFAULT_IMAGE_WIDTH), Some(DEFAULT_IMAGE_HEIGHT)));
+    assert_eq!(parse_image_size_spec("x0"), (Some(DEFAULT_IMAGE_WIDTH), Some(DEFAULT_IMAGE_HEIGHT)));
+}
+
+#[test]
+fn test_list_color_schemes_names_all_schemes() {
+    let mut buf = Vec::new();
+    list_color_schemes(&mut buf, false).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    for name in ["oceanic", "grayscale", "inferno", "viridis", "synthwave", "sunset"] {
+        assert!(text.contains(name), "scheme '{}' missing from the listing", name);
+    }
+    // Stops are printed in #RRGGBB form; plain output carries no ANSI codes
+    assert!(text.contains("#01041B"));
+    assert!(!text.contains('\x1b'));
+}
+
+#[test]
+fn test_parse_dynamic_range_rejects_non_positive() {
+    assert!(parse_dynamic_range("0").unwrap_err().contains("greater than 0"));
+    assert!(parse_dynamic_range("-10").unwrap_err().contains("greater than 0"));
+    assert_eq!(parse_dynamic_range("90"), Ok(CliDynamicRange::Fixed(90.0)));
+}
+
+#[test]
+fn test_histogram_counts_sum_to_cell_count() {
+    let spec_data = scalc::SpectrogramData {
+        data: vec![
+            vec![-80.0, -42.5, -3.0, 0.0],
+            vec![-79.9, -15.0, -15.4, -60.0],
+            vec![-30.0, -30.2, -30.9, -1.5],
+        ],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: scalc::SignalType::Real,
+        hop_length: 512,
+    };
+    let path = std::env::temp_dir().join("sgvr_histogram.csv");
+    export_histogram(&spec_data, path.to_str().unwrap()).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let mut lines = content.lines();
+    assert_eq!(lines.next(), Some("bin_low_db,bin_high_db,count"));
+    let total: usize = lines
+        .map(|line| line.rsplit(',').next().unwrap().parse::<usize>().unwrap())
+        .sum();
+    assert_eq!(total, 12);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_parse_timecode_forms() {
+    assert_eq!(parse_timecode("90"), Ok(90.0));
+    assert_eq!(parse_timecode("12.5"), Ok(12.5));
+    assert_eq!(parse_timecode("1:23"), Ok(83.0));
+    assert_eq!(parse_timecode("02:05.5"), Ok(125.5));
+    assert_eq!(parse_timecode("1:02:03"), Ok(3723.0));
+
+    assert!(parse_timecode("1:2:3:4").is_err());
+    assert!(parse_timecode("1:xx").is_err());
+    assert!(parse_timecode("-5").is_err());
+}
+
+#[test]
+fn test_fft_size_warning_only_for_non_powers_of_two() {
+    assert!(fft_size_warning(2048).is_none());
+    assert!(fft_size_warning(1).is_none());
+    assert!(fft_size_warning(0).is_none());
+
+    let warning = fft_size_warning(3000).unwrap();
+    assert!(warning.contains("3000"));
+    // Suggests the surrounding powers of two
+    assert!(warning.contains("2048"));
+    assert!(warning.contains("4096"));
+}
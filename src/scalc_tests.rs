@@ -39,6 +39,67 @@ fn test_hamming_window_properties() {
     assert!((max_val - 1.0).abs() < 0.01);
 }
 
+#[test]
+fn test_flattop_window_properties() {
+    let window = flattop_window(128);
+    assert_eq!(window.len(), 128);
+
+    // The maximum is 1.0 in the middle; the edges are slightly negative
+    let max_val = window.iter().cloned().fold(f32::MIN, f32::max);
+    assert!((max_val - 1.0).abs() < 0.01);
+    assert!(window[0].abs() < 0.001);
+    assert!((window[0] - window[127]).abs() < 0.001);
+}
+
+#[test]
+fn test_flattop_window_accurate_peak_amplitude() {
+    // A tone placed half a bin off center: the worst case for scalloping loss.
+    // Flat-top must stay within ~0.1 dB of the true level; Hann misses by >1 dB.
+    let n_fft = 1024;
+    let sample_rate = 8000.0;
+    let amplitude = 0.5;
+    let freq = (32.5) * sample_rate / n_fft as f32;
+
+    let path = std::env::temp_dir().join("sgvr_test_flattop.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / sample_rate;
+        let sample = (2.0 * std::f32::consts::PI * freq * time).sin() * amplitude;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let peak_db = |window_type: WindowType| {
+        let params = CalcParams {
+            n_fft,
+            window_size: n_fft,
+            hop_length: 512,
+            window_type,
+            ..Default::default()
+        };
+        let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+        spec_data.data[0].iter().cloned().fold(f32::MIN, f32::max)
+    };
+
+    // With coherent-gain normalization the expected peak level of a tone
+    // is simply amplitude / 2, regardless of the window
+    let expected = 20.0 * (amplitude / 2.0).log10();
+
+    let flattop_error = (peak_db(WindowType::FlatTop) - expected).abs();
+    let hann_error = (peak_db(WindowType::Hann) - expected).abs();
+
+    assert!(flattop_error < 0.1, "flat-top error {} dB", flattop_error);
+    assert!(hann_error > 1.0, "hann error {} dB should show scalloping loss", hann_error);
+
+    std::fs::remove_file(&path).ok();
+}
+
 #[test]
 fn test_calc_params_creation() {
     let params = CalcParams {
@@ -46,6 +107,7 @@ fn test_calc_params_creation() {
         hop_length: 512,
         window_size: 1024,
         window_type: WindowType::Hann,
+        ..Default::default()
     };
     
     assert_eq!(params.n_fft, 1024);
@@ -64,7 +126,7 @@ fn test_window_type_equality() {
 #[test]
 fn test_spectrogram_data_creation() {
     let data = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
-    let spec_data = SpectrogramData { data: data.clone() };
+    let spec_data = SpectrogramData { data: data.clone(), sample_rate: 44100, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
     assert_eq!(spec_data.data, data);
 }
 
@@ -81,6 +143,428 @@ fn test_small_window_sizes() {
     assert!((window_hamming[0] - window_hamming[3]).abs() < 0.001);
 }
 
+/// Write a short 440 Hz test WAV to a temporary path and return it
+fn write_test_wav(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_preview_params_coarser_than_requested() {
+    let params = CalcParams {
+        n_fft: 2048,
+        hop_length: 512,
+        window_size: 2048,
+        window_type: WindowType::Hann,
+        ..Default::default()
+    };
+    let preview = preview_params(&params);
+
+    assert!(preview.n_fft < params.n_fft);
+    assert!(preview.hop_length > params.hop_length);
+    assert_eq!(preview.window_size, preview.n_fft);
+    assert_eq!(preview.window_type, params.window_type);
+}
+
+#[test]
+fn test_fast_preview_has_fewer_frames() {
+    let path = write_test_wav("sgvr_test_preview.wav");
+
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 256,
+        window_size: 1024,
+        window_type: WindowType::Hann,
+        ..Default::default()
+    };
+
+    let full = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let preview = calculate_spectrogram(&path, preview_params(&params), |_, _| {}).unwrap();
+
+    assert!(!full.data.is_empty());
+    assert!(!preview.data.is_empty());
+    assert!(preview.data.len() < full.data.len());
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_spectral_rolloff_low_concentration() {
+    // All energy is in the lowest bins: the rolloff must stay low
+    let mut frame = vec![-180.0; 100];
+    frame[0] = 0.0;
+    frame[1] = -6.0;
+    let spec_data = SpectrogramData { data: vec![frame], sample_rate: 8000, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
+
+    let rolloff = spectral_rolloff(&spec_data, 0.85);
+    assert_eq!(rolloff.len(), 1);
+    assert!(rolloff[0] < 100.0, "rolloff {} should stay near DC", rolloff[0]);
+}
+
+#[test]
+fn test_spectral_rolloff_flat_spectrum() {
+    // A flat (white-noise-like) spectrum rolls off near roll_percent * nyquist
+    let spec_data = SpectrogramData { data: vec![vec![-20.0; 100]], sample_rate: 8000, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
+
+    let rolloff = spectral_rolloff(&spec_data, 0.85);
+    let nyquist = 4000.0;
+    assert!((rolloff[0] - 0.85 * nyquist).abs() < 0.05 * nyquist,
+        "rolloff {} should approach {}", rolloff[0], 0.85 * nyquist);
+}
+
+#[test]
+fn test_diff_spectrograms_crops_to_shorter() {
+    let a = SpectrogramData {
+        data: vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0], vec![7.0, 8.0, 9.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let b = SpectrogramData {
+        data: vec![vec![1.0, 1.0], vec![2.0, 2.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+
+    let diff = diff_spectrograms(&a, &b);
+    assert_eq!(diff.data, vec![vec![0.0, 1.0], vec![2.0, 3.0]]);
+}
+
+#[test]
+fn test_diff_against_self_is_zero_and_renders_central() {
+    let path = write_test_wav("sgvr_test_diff.wav");
+    let params = CalcParams::default();
+
+    let a = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let b = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let diff = diff_spectrograms(&a, &b);
+
+    assert!(diff.data.iter().flatten().all(|&v| v == 0.0));
+
+    // An all-zero diverging render must be the central gradient color everywhere
+    let render_params = crate::srend::RenderParams {
+        width: 8,
+        height: 8,
+        color_scheme: crate::srend::ColorScheme::Diverging,
+        diverging: true,
+        ..Default::default()
+    };
+    let image = crate::srend::create_spectrogram_image(&diff, &render_params);
+    let central = *image.get_pixel(0, 0);
+    assert!(image.pixels().all(|p| *p == central));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_spectrogram_from_f32_wav_matches_i16() {
+    // The AudioReader path normalizes decoded f32 samples directly, so a
+    // float WAV and an i16 WAV of the same tone must agree on the dominant bin
+    let i16_path = write_test_wav("sgvr_test_fmt_i16.wav");
+    let f32_path = std::env::temp_dir().join("sgvr_test_fmt_f32.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(&f32_path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        writer.write_sample((2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams { n_fft: 1024, window_size: 1024, hop_length: 512, ..Default::default() };
+    let from_i16 = calculate_spectrogram(&i16_path, params, |_, _| {}).unwrap();
+    let from_f32 = calculate_spectrogram(&f32_path, params, |_, _| {}).unwrap();
+
+    let dominant_bin = |frame: &[f32]| {
+        frame.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .map(|(k, _)| k)
+            .unwrap()
+    };
+    for (a, b) in from_i16.data.iter().zip(from_f32.data.iter()) {
+        assert_eq!(dominant_bin(a), dominant_bin(b));
+    }
+
+    std::fs::remove_file(&i16_path).ok();
+    std::fs::remove_file(&f32_path).ok();
+}
+
+#[test]
+fn test_signal_shorter_than_window_is_an_error() {
+    let path = std::env::temp_dir().join("sgvr_test_tiny.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for _ in 0..100 {
+        writer.write_sample(0i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let err = calculate_spectrogram(&path, CalcParams::default(), |_, _| {}).unwrap_err();
+    assert!(err.to_string().contains("signal too short: 100 samples, need at least 2048"));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_zero_hop_length_is_an_error() {
+    let path = write_test_wav("sgvr_test_zero_hop.wav");
+    let params = CalcParams { hop_length: 0, ..Default::default() };
+
+    let err = calculate_spectrogram(&path, params, |_, _| {}).unwrap_err();
+    assert!(err.to_string().contains("hop_length must be greater than 0"));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_streaming_long_wav() {
+    // A "multi-minute" file: 2 minutes at 8 kHz. The streaming path keeps only
+    // the current window in memory (checked by a debug_assert inside the loop)
+    // and must still produce the expected number of frames.
+    let path = std::env::temp_dir().join("sgvr_test_streaming.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let total_samples: usize = 8000 * 120;
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..total_samples {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 500.0 * time).sin() * 0.4;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams::default();
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+
+    let expected_frames = (total_samples - params.window_size) / params.hop_length;
+    assert_eq!(spec_data.data.len(), expected_frames);
+    assert_eq!(spec_data.sample_rate, 8000);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_streaming_matches_frame_count_with_large_hop() {
+    // hop_length larger than window_size exercises the skip path
+    let path = write_test_wav("sgvr_test_large_hop.wav");
+    let params = CalcParams {
+        n_fft: 256,
+        window_size: 256,
+        hop_length: 1000,
+        ..Default::default()
+    };
+
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    assert_eq!(spec_data.data.len(), (8000 - 256) / 1000);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_real_fft_matches_complex_fft() {
+    let n_fft = 256;
+
+    // Deterministic multi-tone frame, windowed like the real pipeline
+    let window = hann_window(n_fft);
+    let frame: Vec<f32> = (0..n_fft)
+        .map(|i| {
+            let t = i as f32 / n_fft as f32;
+            let s = (2.0 * std::f32::consts::PI * 5.0 * t).sin() * 0.5
+                + (2.0 * std::f32::consts::PI * 37.0 * t).sin() * 0.3;
+            s * window[i]
+        })
+        .collect();
+
+    // Real path
+    let mut planner = FftPlanner::<f32>::new();
+    let mut real_fft = RealFft::new(&mut planner, n_fft);
+    let mut spectrum = vec![Complex::new(0.0, 0.0); n_fft / 2 + 1];
+    real_fft.process(&frame, &mut spectrum);
+
+    // Reference: full complex FFT, upper half discarded
+    let fft = planner.plan_fft_forward(n_fft);
+    let mut buffer: Vec<Complex<f32>> = frame.iter().map(|&s| Complex::new(s, 0.0)).collect();
+    fft.process(&mut buffer);
+
+    // The two factorizations round differently in f32; complex bins must
+    // agree to within 1e-5 of the peak magnitude, which bounds the dB error
+    // of every non-floor bin well below 0.01 dB
+    let peak = buffer.iter().map(|c| c.norm()).fold(0.0f32, f32::max);
+    let peak_db = magnitude_to_db(peak, DEFAULT_MAG_FLOOR);
+    for (k, bin) in spectrum.iter().enumerate() {
+        let diff = (bin - buffer[k]).norm();
+        assert!(diff < 1e-5 * peak, "bin {}: complex diff {} vs peak {}", k, diff, peak);
+
+        // Bins more than 90 dB below the peak carry only rounding noise,
+        // so the dB comparison is limited to the meaningful range
+        let expected = magnitude_to_db(buffer[k].norm(), DEFAULT_MAG_FLOOR);
+        let actual = magnitude_to_db(bin.norm(), DEFAULT_MAG_FLOOR);
+        if expected > peak_db - 90.0 {
+            assert!((actual - expected).abs() < 0.01,
+                "bin {}: real path {} dB vs complex path {} dB", k, actual, expected);
+        }
+    }
+}
+
+#[test]
+fn test_process_with_scratch_is_bit_identical() {
+    // The reusable-scratch path must produce exactly the same bits as the
+    // allocating `process` call it replaced, over many frames
+    let n_fft = 512;
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(n_fft);
+    let mut scratch = vec![Complex::new(0.0, 0.0); fft.get_inplace_scratch_len()];
+
+    for frame in 0..50 {
+        let input: Vec<Complex<f32>> = (0..n_fft)
+            .map(|i| {
+                let t = (frame * n_fft + i) as f32 / 8000.0;
+                Complex::new((2.0 * std::f32::consts::PI * 440.0 * t).sin() * 0.5, 0.0)
+            })
+            .collect();
+
+        let mut with_alloc = input.clone();
+        fft.process(&mut with_alloc);
+
+        let mut with_scratch = input;
+        fft.process_with_scratch(&mut with_scratch, &mut scratch);
+
+        for (a, b) in with_alloc.iter().zip(with_scratch.iter()) {
+            assert_eq!(a.re.to_bits(), b.re.to_bits());
+            assert_eq!(a.im.to_bits(), b.im.to_bits());
+        }
+    }
+}
+
+#[test]
+fn test_real_fft_zero_padding() {
+    // A frame shorter than n_fft must be treated as zero-padded
+    let n_fft = 64;
+    let frame: Vec<f32> = (0..48).map(|i| (i as f32 * 0.1).sin()).collect();
+
+    let mut planner = FftPlanner::<f32>::new();
+    let mut real_fft = RealFft::new(&mut planner, n_fft);
+    let mut spectrum = vec![Complex::new(0.0, 0.0); n_fft / 2 + 1];
+    real_fft.process(&frame, &mut spectrum);
+
+    let fft = planner.plan_fft_forward(n_fft);
+    let mut buffer = vec![Complex::new(0.0, 0.0); n_fft];
+    for (buf, &s) in buffer.iter_mut().zip(frame.iter()) {
+        *buf = Complex::new(s, 0.0);
+    }
+    fft.process(&mut buffer);
+
+    for (k, bin) in spectrum.iter().enumerate() {
+        assert!((bin.norm() - buffer[k].norm()).abs() < 1e-4, "bin {} mismatch", k);
+    }
+}
+
+#[test]
+fn test_cache_roundtrip_produces_identical_image() {
+    let wav_path = write_test_wav("sgvr_test_cache.wav");
+    let cache_path = std::env::temp_dir().join("sgvr_test_cache.sgvc");
+    let params = CalcParams::default();
+
+    let fresh = calculate_spectrogram(&wav_path, params, |_, _| {}).unwrap();
+    save_cache(&fresh, &params, &cache_path).unwrap();
+
+    let cached = load_cache(&cache_path, &params).expect("cache should load");
+    assert_eq!(cached.sample_rate, fresh.sample_rate);
+    assert_eq!(cached.data, fresh.data);
+
+    // The image rendered from the cache must be identical to the fresh one
+    let render_params = crate::srend::RenderParams { width: 32, height: 16, ..Default::default() };
+    let fresh_image = crate::srend::create_spectrogram_image(&fresh, &render_params);
+    let cached_image = crate::srend::create_spectrogram_image(&cached, &render_params);
+    assert_eq!(fresh_image.as_raw(), cached_image.as_raw());
+
+    std::fs::remove_file(&wav_path).ok();
+    std::fs::remove_file(&cache_path).ok();
+}
+
+#[test]
+fn test_cache_invalidated_by_different_params() {
+    let wav_path = write_test_wav("sgvr_test_cache_inval.wav");
+    let cache_path = std::env::temp_dir().join("sgvr_test_cache_inval.sgvc");
+    let params = CalcParams::default();
+
+    let fresh = calculate_spectrogram(&wav_path, params, |_, _| {}).unwrap();
+    save_cache(&fresh, &params, &cache_path).unwrap();
+
+    let other_params = CalcParams { n_fft: 1024, ..params };
+    assert!(load_cache(&cache_path, &other_params).is_none());
+
+    std::fs::remove_file(&wav_path).ok();
+    std::fs::remove_file(&cache_path).ok();
+}
+
+#[test]
+fn test_magnitude_to_db_floor_controls_silent_bins() {
+    // A near-silent bin follows the floor: lowering it gives more negative dB
+    let high_floor = magnitude_to_db(0.0, 1.0e-9);
+    let low_floor = magnitude_to_db(0.0, 1.0e-12);
+    assert!((high_floor - -180.0).abs() < 0.001);
+    assert!((low_floor - -240.0).abs() < 0.001);
+    assert!(low_floor < high_floor);
+
+    // A loud bin is unaffected by the floor
+    assert_eq!(magnitude_to_db(1.0, 1.0e-9), magnitude_to_db(1.0, 1.0e-12));
+}
+
+#[test]
+fn test_validate_samples_strict_rejects_nan() {
+    let mut samples = vec![0.1, f32::NAN, 0.2];
+    let err = validate_samples(&mut samples, true, 0).unwrap_err();
+    assert!(err.to_string().contains("index 1"));
+}
+
+#[test]
+fn test_validate_samples_strict_rejects_out_of_range() {
+    let mut samples = vec![0.5, -1.5, 0.2];
+    let err = validate_samples(&mut samples, true, 0).unwrap_err();
+    assert!(err.to_string().contains("index 1"));
+    assert!(err.to_string().contains("-1.5"));
+}
+
+#[test]
+fn test_validate_samples_lenient_clamps_nan() {
+    let mut samples = vec![0.1, f32::NAN, f32::INFINITY, 0.2];
+    validate_samples(&mut samples, false, 0).unwrap();
+    assert_eq!(samples, vec![0.1, 0.0, 0.0, 0.2]);
+}
+
 #[test]
 fn test_zero_size_window() {
     let window = hann_window(0);
@@ -99,4 +583,1282 @@ fn test_single_size_window() {
     // This is correct behavior - a window of size 1 is rarely used in real applications
     assert!(window_hann[0].is_nan());
     assert!(window_hamming[0].is_nan());
-}
\ No newline at end of file
+}
+#[test]
+fn test_phase_not_computed_by_default() {
+    let path = write_test_wav("sgvr_test_no_phase.wav");
+    let params = CalcParams { n_fft: 512, window_size: 512, hop_length: 256, ..Default::default() };
+
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    assert!(spec_data.phase.is_none());
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_phase_progression_of_single_bin_tone() {
+    // A tone centered exactly on bin 1 (fs / n_fft Hz): its phase must advance
+    // by 2*pi * bin * hop / n_fft = pi/2 radians between consecutive frames
+    let n_fft = 256;
+    let hop = 64;
+    let freq = 8000.0 / n_fft as f32;
+
+    let path = std::env::temp_dir().join("sgvr_test_phase.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * freq * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft,
+        window_size: n_fft,
+        hop_length: hop,
+        compute_phase: true,
+        ..Default::default()
+    };
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+
+    let phase = spec_data.phase.as_ref().unwrap();
+    assert_eq!(phase.len(), spec_data.data.len());
+    assert_eq!(phase[0].len(), n_fft / 2 + 1);
+
+    let expected_advance = std::f32::consts::FRAC_PI_2;
+    for pair in phase.windows(2).take(20) {
+        let mut advance = pair[1][1] - pair[0][1];
+        // Wrap the difference into (-pi, pi] before comparing
+        while advance <= -std::f32::consts::PI {
+            advance += 2.0 * std::f32::consts::PI;
+        }
+        while advance > std::f32::consts::PI {
+            advance -= 2.0 * std::f32::consts::PI;
+        }
+        assert!(
+            (advance - expected_advance).abs() < 0.05,
+            "phase advance {} differs from expected {}", advance, expected_advance
+        );
+    }
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_power_db_scale_relates_to_amplitude_scale() {
+    // Amplitude dB is normalized by the coherent gain, power dB by the window
+    // energy, so the two scales differ by 10*log10(sum(w^2)) - 20*log10(sum(w))
+    let path = write_test_wav("sgvr_test_db_scale.wav");
+    let n_fft = 1024;
+    let base = CalcParams { n_fft, window_size: n_fft, hop_length: 512, ..Default::default() };
+
+    let amp = calculate_spectrogram(&path, base, |_, _| {}).unwrap();
+    let pow = calculate_spectrogram(&path, CalcParams { db_scale: DbScale::Power, ..base }, |_, _| {}).unwrap();
+
+    let peak = |frame: &[f32]| frame.iter().cloned().fold(f32::MIN, f32::max);
+    let window = hann_window(n_fft);
+    let window_sum_sq: f32 = window.iter().map(|w| w * w).sum();
+    let coherent_gain: f32 = window.iter().sum();
+    let expected_offset = 10.0 * window_sum_sq.log10() - 20.0 * coherent_gain.log10();
+
+    let offset = peak(&amp.data[0]) - peak(&pow.data[0]);
+    assert!(
+        (offset - expected_offset).abs() < 0.01,
+        "offset {} dB differs from expected {} dB", offset, expected_offset
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_peak_db_is_window_independent() {
+    // Coherent-gain normalization: the same tone must read the same peak dB
+    // regardless of the window function
+    let path = write_test_wav("sgvr_test_win_gain.wav");
+    let peak_db = |window_type: WindowType| {
+        let params = CalcParams {
+            n_fft: 1024,
+            window_size: 1024,
+            hop_length: 512,
+            window_type,
+            ..Default::default()
+        };
+        let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+        spec_data.data[0].iter().cloned().fold(f32::MIN, f32::max)
+    };
+
+    let hann = peak_db(WindowType::Hann);
+    let hamming = peak_db(WindowType::Hamming);
+    assert!((hann - hamming).abs() < 0.2, "hann {} dB vs hamming {} dB", hann, hamming);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_db_ref_calibrates_peak_to_zero() {
+    // Using the peak magnitude itself as the reference must read ~0 dB there
+    let path = write_test_wav("sgvr_test_db_ref.wav");
+    let params = CalcParams { n_fft: 1024, window_size: 1024, hop_length: 512, ..Default::default() };
+
+    let baseline = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let peak_db = baseline.data[0].iter().cloned().fold(f32::MIN, f32::max);
+
+    let calibrated = calculate_spectrogram(
+        &path,
+        CalcParams { db_ref: 10.0f32.powf(peak_db / 20.0), ..params },
+        |_, _| {},
+    ).unwrap();
+    let calibrated_peak = calibrated.data[0].iter().cloned().fold(f32::MIN, f32::max);
+
+    assert!(calibrated_peak.abs() < 1e-3, "calibrated peak {} dB should be ~0", calibrated_peak);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_mel_filterbank_shape() {
+    let filters = mel_filterbank(40, 513, 8000);
+    assert_eq!(filters.len(), 40);
+    assert!(filters.iter().all(|f| f.len() == 513));
+    // Every filter must carry some weight
+    assert!(filters.iter().all(|f| f.iter().sum::<f32>() > 0.0));
+}
+
+#[test]
+fn test_mel_bands_row_count_and_tone_position() {
+    let path = write_test_wav("sgvr_test_mel.wav");
+    let params = CalcParams {
+        n_fft: 1024,
+        window_size: 1024,
+        hop_length: 512,
+        mel_bands: Some(40),
+        ..Default::default()
+    };
+
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    assert!(spec_data.data.iter().all(|col| col.len() == 40));
+
+    // A 440 Hz tone at 8 kHz lies in the lower half of the mel axis
+    let dominant_band = spec_data.data[0].iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+        .map(|(k, _)| k)
+        .unwrap();
+    assert!(dominant_band < 20, "dominant mel band {} should be in the lower half", dominant_band);
+
+    std::fs::remove_file(&path).ok();
+}
+
+/// Write a 2-second 440 Hz test WAV at 8 kHz and return its path
+fn write_two_second_wav(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..16000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
+
+#[test]
+fn test_time_range_limits_frame_count() {
+    // A 1-second slice of a 2-second file must produce exactly the frames
+    // that fit into the selected duration
+    let path = write_two_second_wav("sgvr_test_time_range.wav");
+    let params = CalcParams {
+        n_fft: 1024,
+        window_size: 1024,
+        hop_length: 512,
+        start_time: Some(0.5),
+        end_time: Some(1.5),
+        ..Default::default()
+    };
+
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let range_samples = 8000; // 1 second at 8 kHz
+    assert_eq!(spec_data.data.len(), (range_samples - 1024) / 512);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_time_range_clamps_to_file_duration() {
+    // An end time past the file clamps to the actual duration
+    let path = write_two_second_wav("sgvr_test_time_clamp.wav");
+    let params = CalcParams {
+        n_fft: 1024,
+        window_size: 1024,
+        hop_length: 512,
+        start_time: Some(0.5),
+        end_time: Some(100.0),
+        ..Default::default()
+    };
+
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let range_samples = 16000 - 4000; // 0.5s..2.0s at 8 kHz
+    assert_eq!(spec_data.data.len(), (range_samples - 1024) / 512);
+
+    std::fs::remove_file(&path).ok();
+}
+
+/// Write a raw f32 I/Q file with a complex exponential at the given frequency
+fn write_iq_exponential(name: &str, freq: f32) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    let mut bytes = Vec::new();
+    for t in 0..8000 {
+        let phase = 2.0 * std::f32::consts::PI * freq * t as f32 / 8000.0;
+        bytes.extend_from_slice(&(phase.cos() * 0.5).to_le_bytes());
+        bytes.extend_from_slice(&(phase.sin() * 0.5).to_le_bytes());
+    }
+    std::fs::write(&path, &bytes).unwrap();
+    path
+}
+
+#[test]
+fn test_iq_two_sided_spectrum_separates_signs() {
+    // A complex exponential at +f lands above the centered DC bin, one at -f
+    // symmetrically below it; a real FFT could not tell the two apart
+    let n_fft = 256;
+    let pos_path = write_iq_exponential("sgvr_test_iq_pos.iqw", 1000.0);
+    let neg_path = write_iq_exponential("sgvr_test_iq_neg.iqw", -1000.0);
+
+    let params = CalcParams {
+        n_fft,
+        window_size: n_fft,
+        hop_length: 128,
+        signal_type: SignalType::Iq,
+        raw_input: Some(RawInputParams {
+            sample_rate: 8000,
+            sample_format: crate::audio::RawSampleFormat::F32,
+            endian: crate::audio::RawEndian::Le,
+            iq_order: crate::audio::IqOrder::Iq,
+        }),
+        ..Default::default()
+    };
+
+    let dominant_bin = |path: &std::path::Path| {
+        let spec_data = calculate_spectrogram(path, params, |_, _| {}).unwrap();
+        // The full two-sided spectrum is kept
+        assert!(spec_data.data.iter().all(|col| col.len() == n_fft));
+        spec_data.data[0].iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .map(|(k, _)| k)
+            .unwrap()
+    };
+
+    // DC sits at n_fft / 2 after the fftshift; 1000 Hz at 8 kHz is 32 bins out
+    let center = n_fft / 2;
+    let offset = 1000 * n_fft / 8000;
+    assert_eq!(dominant_bin(&pos_path), center + offset);
+    assert_eq!(dominant_bin(&neg_path), center - offset);
+
+    std::fs::remove_file(&pos_path).ok();
+    std::fs::remove_file(&neg_path).ok();
+}
+
+#[test]
+fn test_iq_rejects_mel_bands() {
+    let path = write_iq_exponential("sgvr_test_iq_mel.iqw", 500.0);
+    let params = CalcParams {
+        signal_type: SignalType::Iq,
+        mel_bands: Some(40),
+        raw_input: Some(RawInputParams {
+            sample_rate: 8000,
+            sample_format: crate::audio::RawSampleFormat::F32,
+            endian: crate::audio::RawEndian::Le,
+            iq_order: crate::audio::IqOrder::Iq,
+        }),
+        ..Default::default()
+    };
+
+    let err = calculate_spectrogram(&path, params, |_, _| {}).unwrap_err();
+    assert!(err.to_string().contains("mel bands are not supported for I/Q input"));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_remove_dc_drops_bin_zero() {
+    // A tone riding on a constant offset: bin 0 is dominated by the bias
+    // unless the per-frame mean is removed first
+    let path = std::env::temp_dir().join("sgvr_test_dc.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = 0.3 + (2.0 * std::f32::consts::PI * 440.0 * time).sin() * 0.4;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams { n_fft: 1024, window_size: 1024, hop_length: 512, ..Default::default() };
+    let bin_zero = |remove_dc: bool| {
+        let spec_data = calculate_spectrogram(&path, CalcParams { remove_dc, ..params }, |_, _| {}).unwrap();
+        spec_data.data[0][0]
+    };
+
+    let with_dc = bin_zero(false);
+    let without_dc = bin_zero(true);
+    assert!(
+        with_dc - without_dc > 40.0,
+        "removing DC should drop bin 0 substantially ({} dB vs {} dB)", with_dc, without_dc
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_center_shows_transient_at_sample_zero() {
+    // An impulse at sample 0 sits at the edge of the first uncentered frame,
+    // where the Hann window is ~0, so it vanishes; with centering the first
+    // frame is centered at time 0 and the impulse gets full window weight
+    let path = std::env::temp_dir().join("sgvr_test_center.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    writer.write_sample((0.9 * i16::MAX as f32) as i16).unwrap();
+    for _ in 1..8000 {
+        writer.write_sample(0i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams { n_fft: 256, window_size: 256, hop_length: 64, ..Default::default() };
+    let first_column_peak = |center: bool| {
+        let spec_data = calculate_spectrogram(&path, CalcParams { center, ..params }, |_, _| {}).unwrap();
+        if center {
+            // Centered output has one frame per hop position plus frame 0
+            assert_eq!(spec_data.data.len(), 8000 / 64 + 1);
+        }
+        spec_data.data[0].iter().cloned().fold(f32::MIN, f32::max)
+    };
+
+    let centered = first_column_peak(true);
+    let uncentered = first_column_peak(false);
+    assert!(centered > -60.0, "centered first column peak {} dB should show the impulse", centered);
+    assert!(uncentered < -100.0, "uncentered first column peak {} dB should be near the floor", uncentered);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_unknown_total_samples_streams_to_eof() {
+    // A reader that cannot report its length must still yield every frame
+    // that fits, instead of producing nothing
+    struct NoMetaReader {
+        samples: Vec<f32>,
+        pos: usize,
+    }
+
+    impl crate::audio::AudioReader for NoMetaReader {
+        fn sample_rate(&self) -> u32 {
+            8000
+        }
+        fn total_samples(&self) -> Option<usize> {
+            None
+        }
+        fn read(&mut self, out: &mut [f32]) -> Result<usize, Box<dyn std::error::Error>> {
+            let n = out.len().min(self.samples.len() - self.pos);
+            out[..n].copy_from_slice(&self.samples[self.pos..self.pos + n]);
+            self.pos += n;
+            Ok(n)
+        }
+    }
+
+    let total = 8000;
+    let samples: Vec<f32> = (0..total)
+        .map(|t| (2.0 * std::f32::consts::PI * 440.0 * t as f32 / 8000.0).sin() * 0.5)
+        .collect();
+    let mut reader = NoMetaReader { samples, pos: 0 };
+
+    let params = CalcParams { n_fft: 1024, window_size: 1024, hop_length: 512, ..Default::default() };
+    let spec_data = calculate_spectrogram_from_reader(&mut reader, params, |_, _| {}).unwrap();
+
+    // Every window that fully fits yields a frame
+    assert_eq!(spec_data.data.len(), (total - 1024) / 512 + 1);
+    assert_eq!(spec_data.sample_rate, 8000);
+}
+
+#[test]
+fn test_f64_precision_matches_f32_peak_location() {
+    // 8192-point FFT of a tone placed exactly on bin 1000: both precisions
+    // must localize the peak there, and the f64 dB values must stay close
+    // to the f32 ones (the pipelines differ only in accumulation error)
+    let path = std::env::temp_dir().join("sgvr_test_precision.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    let freq = 1000.0 * 8000.0 / 8192.0;
+    for t in 0..16384 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * freq * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft: 8192,
+        hop_length: 8192,
+        window_size: 8192,
+        ..Default::default()
+    };
+    let f32_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let f64_data = calculate_spectrogram(
+        &path,
+        CalcParams { precision: Precision::F64, ..params },
+        |_, _| {},
+    ).unwrap();
+
+    let peak_bin = |frame: &[f32]| {
+        frame.iter().enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap()
+    };
+    assert_eq!(peak_bin(&f32_data.data[0]), 1000);
+    assert_eq!(peak_bin(&f64_data.data[0]), 1000);
+
+    // Away from the noise floor the two paths agree to a fraction of a dB
+    for (a, b) in f32_data.data[0].iter().zip(f64_data.data[0].iter()) {
+        if *a > -100.0 {
+            assert!((a - b).abs() < 0.5, "f32 {} vs f64 {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn test_zero_padding_doubles_frequency_bins() {
+    // Same 1024-sample window, FFT zero-padded to 2048 points: twice the
+    // bins on the same data, with the tone's peak bin index doubling too
+    let path = write_test_wav("sgvr_test_zero_pad.wav");
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 256,
+        window_size: 1024,
+        ..Default::default()
+    };
+    let plain = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let padded = calculate_spectrogram(
+        &path,
+        CalcParams { n_fft: 2048, ..params },
+        |_, _| {},
+    ).unwrap();
+
+    assert_eq!(plain.data[0].len(), 1024 / 2 + 1);
+    assert_eq!(padded.data[0].len(), 2048 / 2 + 1);
+    assert_eq!(plain.data.len(), padded.data.len());
+
+    let peak_bin = |frame: &[f32]| {
+        frame.iter().enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap()
+    };
+    // 440 Hz at 8000 Hz: bin 56 of 1024 points, bin 113 of 2048 points
+    let plain_peak = peak_bin(&plain.data[0]);
+    let padded_peak = peak_bin(&padded.data[0]);
+    assert!(padded_peak.abs_diff(plain_peak * 2) <= 1);
+}
+
+#[test]
+fn test_bin_frequencies_real_and_iq_layout() {
+    // Real layout: 513 one-sided bins of a 1024-point FFT at 8 kHz
+    let real = SpectrogramData {
+        data: vec![vec![0.0; 513]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let freqs = real.bin_frequencies();
+    assert_eq!(freqs.len(), 513);
+    assert_eq!(freqs[0], 0.0);
+    assert_eq!(freqs[512], 4000.0);
+    assert!((freqs[1] - 8000.0 / 1024.0).abs() < 1e-3);
+
+    // I/Q layout: 256 fftshifted bins, DC in the middle
+    let iq = SpectrogramData {
+        data: vec![vec![0.0; 256]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Iq,
+        hop_length: 512,
+    };
+    let freqs = iq.bin_frequencies();
+    assert_eq!(freqs.len(), 256);
+    assert_eq!(freqs[0], -4000.0);
+    assert_eq!(freqs[128], 0.0);
+    assert!((freqs[255] - (4000.0 - 8000.0 / 256.0)).abs() < 1e-3);
+}
+
+#[test]
+fn test_frame_times_spacing_matches_hop() {
+    let spec_data = SpectrogramData {
+        data: vec![vec![0.0; 4]; 10],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 256,
+    };
+    let times = spec_data.frame_times(256);
+    assert_eq!(times.len(), 10);
+    assert_eq!(times[0], 0.0);
+    let expected_step = 256.0 / 8000.0;
+    for pair in times.windows(2) {
+        assert!((pair[1] - pair[0] - expected_step).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_progress_reports_every_frame_up_to_total() {
+    // A short file reports on every frame: processed must increase strictly
+    // monotonically and the final call must land exactly on total
+    let path = write_test_wav("sgvr_test_progress.wav");
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 256,
+        window_size: 1024,
+        ..Default::default()
+    };
+
+    let mut calls: Vec<(usize, usize)> = Vec::new();
+    calculate_spectrogram(&path, params, |processed, total| {
+        calls.push((processed, total));
+    }).unwrap();
+
+    let expected_frames = (8000 - 1024) / 256;
+    assert_eq!(calls.len(), expected_frames);
+    for (k, (processed, total)) in calls.iter().enumerate() {
+        assert_eq!(*processed, k + 1);
+        assert_eq!(*total, expected_frames);
+    }
+    assert_eq!(calls.last().unwrap().0, expected_frames);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_planner_reuses_cached_plan_for_same_size() {
+    // Two requests for the same FFT size must come out of the planner's
+    // cache as the same instance, not a freshly planned copy
+    let mut calculator = SpectrogramCalculator::new();
+    let first = calculator.plan_forward(1024);
+    let second = calculator.plan_forward(1024);
+    assert!(Arc::ptr_eq(&first, &second));
+
+    // A different size gets its own plan
+    let other = calculator.plan_forward(2048);
+    assert!(!Arc::ptr_eq(&first, &other));
+}
+
+#[test]
+fn test_welch_average_flattens_white_noise() {
+    // Deterministic pseudo-noise WAV: averaging its frame power spectra
+    // must converge to a flat line, unlike any single noisy frame
+    let path = std::env::temp_dir().join("sgvr_test_welch.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    let mut state = 0x1234_5678u32;
+    for _ in 0..16000 {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let sample = (state >> 8) as f32 / (1 << 24) as f32 - 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft: 256,
+        hop_length: 128,
+        window_size: 256,
+        ..Default::default()
+    };
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let averaged = welch_average(&spec_data);
+
+    assert_eq!(averaged.data.len(), 1);
+    assert_eq!(averaged.data[0].len(), spec_data.data[0].len());
+    assert_eq!(averaged.sample_rate, spec_data.sample_rate);
+
+    // Interior bins of ~120 averaged periodograms stay within a few dB
+    // of their median; a single frame scatters over tens of dB
+    let mut bins: Vec<f32> = averaged.data[0][1..averaged.data[0].len() - 1].to_vec();
+    bins.sort_unstable_by(f32::total_cmp);
+    let median = bins[bins.len() / 2];
+    for &db in &bins {
+        assert!((db - median).abs() < 4.0, "bin {} dB vs median {} dB", db, median);
+    }
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_peak_bins_finds_dominant_tone() {
+    let path = std::env::temp_dir().join("sgvr_test_peak_bins.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for i in 0..16000 {
+        let t = i as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 220.0 * t).sin() * 0.8;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 512,
+        window_size: 1024,
+        ..Default::default()
+    };
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let peaks = peak_bins(&spec_data, 1);
+
+    // 220 Hz at 8 kHz with a 1024-point FFT lands in bin 220 / (8000 / 1024) ~= 28
+    assert_eq!(peaks.len(), 1);
+    let expected = (220.0 * 1024.0 / 8000.0_f32).round() as usize;
+    assert!(
+        peaks[0].abs_diff(expected) <= 1,
+        "peak at bin {}, expected ~{}",
+        peaks[0],
+        expected
+    );
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_error_variants_are_matchable() {
+    // Io: the input file does not exist
+    let err = calculate_spectrogram(
+        Path::new("/nonexistent/sgvr_missing.wav"), CalcParams::default(), |_, _| {},
+    ).unwrap_err();
+    assert!(matches!(err, ScalcError::Io(_)), "expected Io, got {:?}", err);
+
+    // InvalidParams: zero hop length
+    let path = write_test_wav("sgvr_test_err_variants.wav");
+    let params = CalcParams { hop_length: 0, ..Default::default() };
+    let err = calculate_spectrogram(&path, params, |_, _| {}).unwrap_err();
+    assert!(matches!(err, ScalcError::InvalidParams(_)), "expected InvalidParams, got {:?}", err);
+
+    // TooShort: one window does not fit into the selected time range
+    let params = CalcParams { end_time: Some(0.01), ..Default::default() };
+    let err = calculate_spectrogram(&path, params, |_, _| {}).unwrap_err();
+    assert!(
+        matches!(err, ScalcError::TooShort { samples: 80, needed: 2048 }),
+        "expected TooShort, got {:?}",
+        err
+    );
+    std::fs::remove_file(&path).ok();
+
+    // Decode: strict mode rejects an out-of-range float sample
+    let path = std::env::temp_dir().join("sgvr_test_err_decode.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for _ in 0..4096 {
+        writer.write_sample(2.0f32).unwrap();
+    }
+    writer.finalize().unwrap();
+    let params = CalcParams { strict: true, ..Default::default() };
+    let err = calculate_spectrogram(&path, params, |_, _| {}).unwrap_err();
+    assert!(matches!(err, ScalcError::Decode(_)), "expected Decode, got {:?}", err);
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_params_builder_happy_path() {
+    let params = CalcParams::builder()
+        .n_fft(4096)
+        .hop_length(1024)
+        .window_type(WindowType::Hamming)
+        .build()
+        .unwrap();
+
+    assert_eq!(params.n_fft, 4096);
+    assert_eq!(params.hop_length, 1024);
+    // window_size follows n_fft unless set explicitly
+    assert_eq!(params.window_size, 4096);
+    assert_eq!(params.window_type, WindowType::Hamming);
+}
+
+#[test]
+fn test_params_builder_rejects_bad_combinations() {
+    let cases: Vec<(CalcParamsBuilder, &str)> = vec![
+        (CalcParams::builder().hop_length(0), "hop_length"),
+        (CalcParams::builder().window_size(0), "window_size"),
+        (CalcParams::builder().n_fft(1024).window_size(2048), "exceeds n_fft"),
+        (CalcParams::builder().n_fft(1), "n_fft"),
+        (CalcParams::builder().n_fft(MAX_N_FFT + 1), "n_fft"),
+    ];
+    for (builder, expected) in cases {
+        let err = builder.build().unwrap_err();
+        assert!(matches!(err, ScalcError::InvalidParams(_)), "got {:?}", err);
+        assert!(err.to_string().contains(expected), "{} not in {}", expected, err);
+    }
+}
+
+#[test]
+fn test_resampled_tone_keeps_its_frequency() {
+    let path = write_test_wav("sgvr_test_resample.wav");
+
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 512,
+        window_size: 1024,
+        resample: Some(4000),
+        ..Default::default()
+    };
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+
+    // The data carries the target rate, so axis labels follow it
+    assert_eq!(spec_data.sample_rate, 4000);
+
+    // The 440 Hz tone must stay at 440 Hz on the new axis, within one bin
+    let peak_bin = spec_data.data[0]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap();
+    let bin_width = 4000.0 / 1024.0;
+    let peak_hz = peak_bin as f32 * bin_width;
+    assert!((peak_hz - 440.0).abs() <= bin_width, "peak at {} Hz", peak_hz);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_preview_decimation_reduces_frames_by_the_factor() {
+    let path = write_test_wav("sgvr_test_decimate.wav");
+    let base = CalcParams {
+        n_fft: 512,
+        hop_length: 256,
+        window_size: 512,
+        ..Default::default()
+    };
+
+    let full = calculate_spectrogram(&path, base, |_, _| {}).unwrap();
+    let preview = calculate_spectrogram(
+        &path, CalcParams { decimate: Some(4), ..base }, |_, _| {}).unwrap();
+
+    // 8000 samples decimated 4x leave 2000; frame counts follow the same
+    // (total - window) / hop formula on the reduced stream
+    assert_eq!(full.data.len(), (8000 - 512) / 256);
+    assert_eq!(preview.data.len(), (8000 / 4 - 512) / 256);
+    assert_eq!(preview.sample_rate, 2000);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_auto_decimation_targets_frame_count() {
+    // 8000 frames worth of input aims for a quarter of it
+    assert_eq!(auto_decimation(512 * PREVIEW_TARGET_FRAMES * 4, 512), 4);
+    // Short files are left untouched
+    assert_eq!(auto_decimation(8000, 512), 1);
+}
+
+#[test]
+fn test_spectrogram_from_in_memory_samples() {
+    // 8000 samples of a tone centered exactly on bin 64 of a 1024-point FFT
+    let samples: Vec<f32> = (0..8000)
+        .map(|i| (2.0 * std::f32::consts::PI * 64.0 * i as f32 / 1024.0).sin() * 0.5)
+        .collect();
+    let params = CalcParams {
+        n_fft: 1024,
+        hop_length: 512,
+        window_size: 1024,
+        ..Default::default()
+    };
+
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, 8000, params, |_, _| {}).unwrap();
+
+    assert_eq!(spec_data.sample_rate, 8000);
+    assert_eq!(spec_data.data.len(), (8000 - 1024) / 512);
+    let peak_bin = spec_data.data[0]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap();
+    assert_eq!(peak_bin, 64);
+}
+
+#[test]
+fn test_detect_chirp_recovers_linear_sweep_rate() {
+    // Linear chirp 500 -> 2000 Hz over 2 s at 8 kHz: rate 750 Hz/s
+    let sample_rate = 8000u32;
+    let duration = 2.0f32;
+    let (f0, f1) = (500.0f32, 2000.0f32);
+    let samples: Vec<f32> = (0..(sample_rate as f32 * duration) as usize)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            // Phase is the integral of the instantaneous frequency
+            let phase = 2.0 * std::f32::consts::PI
+                * (f0 * t + (f1 - f0) * t * t / (2.0 * duration));
+            phase.sin() * 0.5
+        })
+        .collect();
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 128,
+        window_size: 512,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, sample_rate, params, |_, _| {}).unwrap();
+
+    let fit = detect_chirp(&spec_data).expect("a clean chirp must be detected");
+    let expected_rate = (f1 - f0) / duration;
+    assert!(
+        (fit.rate_hz_per_s - expected_rate).abs() < expected_rate * 0.1,
+        "fitted rate {} Hz/s is not within 10% of {}",
+        fit.rate_hz_per_s,
+        expected_rate
+    );
+    assert!(fit.end_hz > fit.start_hz);
+}
+
+#[test]
+fn test_detect_chirp_rejects_noise() {
+    // Deterministic pseudo-noise has no persistent ridge to fit
+    let mut state = 0x1234_5678u32;
+    let samples: Vec<f32> = (0..16000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state as f32 / u32::MAX as f32) - 0.5
+        })
+        .collect();
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 128,
+        window_size: 512,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, 8000, params, |_, _| {}).unwrap();
+
+    assert_eq!(detect_chirp(&spec_data), None);
+}
+
+#[test]
+fn test_clipped_frames_are_flagged() {
+    // A loud tone driven past full scale saturates; a quiet one does not
+    let path = std::env::temp_dir().join("sgvr_test_clipped.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        // First half clips hard at full scale, second half stays clean
+        let gain = if t < 4000 { 2.0 } else { 0.3 };
+        let sample = (2.0 * std::f32::consts::PI * 440.0 * time).sin() * gain;
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 256,
+        window_size: 512,
+        ..Default::default()
+    };
+    let mut calculator = SpectrogramCalculator::new();
+    let spec_data = calculator.calculate(&path, params, |_, _| {}).unwrap();
+
+    assert_eq!(spec_data.clipped.len(), spec_data.data.len());
+    assert!(spec_data.clipped.first().copied().unwrap(), "saturated frames must be flagged");
+    assert!(!spec_data.clipped.last().copied().unwrap(), "clean frames must not be flagged");
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_istft_reconstructs_tone_within_tolerance() {
+    // STFT -> ISTFT of a 440 Hz tone; the middle of the signal (where the
+    // hann/4-hop overlap-add fully covers every sample) must match closely
+    let sample_rate = 8000u32;
+    let samples: Vec<f32> = (0..8000)
+        .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 8000.0).sin() * 0.5)
+        .collect();
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 128,
+        window_size: 512,
+        compute_phase: true,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, sample_rate, params, |_, _| {}).unwrap();
+
+    let rebuilt = resynthesize(&spec_data, &params).unwrap();
+
+    // Ignore one window on each edge, where overlap-add is incomplete
+    let margin = params.window_size;
+    let len = rebuilt.len().min(samples.len());
+    assert!(len > 2 * margin, "reconstruction too short: {} samples", len);
+    let mut err_sq = 0.0f64;
+    for n in margin..len - margin {
+        err_sq += ((rebuilt[n] - samples[n]) as f64).powi(2);
+    }
+    let rms_error = (err_sq / (len - 2 * margin) as f64).sqrt();
+    assert!(rms_error < 0.01, "RMS reconstruction error too high: {}", rms_error);
+}
+
+#[test]
+fn test_istft_requires_phase_data() {
+    let samples = vec![0.25f32; 4096];
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 128,
+        window_size: 512,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, 8000, params, |_, _| {}).unwrap();
+
+    let err = resynthesize(&spec_data, &params).unwrap_err();
+    assert!(matches!(err, ScalcError::InvalidParams(_)));
+    assert!(err.to_string().contains("compute_phase"));
+}
+
+#[test]
+fn test_denoise_lowers_floor_but_keeps_tone() {
+    // A bin-centered tone over deterministic stationary noise
+    let mut state = 0x8765_4321u32;
+    let samples: Vec<f32> = (0..16000)
+        .map(|i| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let noise = ((state as f32 / u32::MAX as f32) - 0.5) * 0.1;
+            (2.0 * std::f32::consts::PI * 64.0 * i as f32 / 512.0).sin() * 0.5 + noise
+        })
+        .collect();
+    let params = CalcParams {
+        n_fft: 512,
+        hop_length: 256,
+        window_size: 512,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, 8000, params, |_, _| {}).unwrap();
+
+    let denoised = spectral_denoise(&spec_data);
+
+    let mean_db = |data: &SpectrogramData, bin: usize| {
+        data.data.iter().map(|frame| frame[bin] as f64).sum::<f64>() / data.data.len() as f64
+    };
+    // The tone bin barely moves (the noise under it is tiny in comparison)
+    assert!((mean_db(&spec_data, 64) - mean_db(&denoised, 64)).abs() < 2.0);
+    // A noise-only bin far from the tone drops substantially
+    assert!(mean_db(&spec_data, 200) - mean_db(&denoised, 200) > 20.0);
+}
+
+#[test]
+fn test_sample_at_round_trips_known_cell() {
+    // Frame 2, bin 3 gets a marker value; 8 kHz real data with 512 bins per
+    // side means a 1022-point FFT -> bin spacing sample_rate / 1022
+    let mut data = vec![vec![-60.0f32; 512]; 4];
+    data[2][3] = -12.5;
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let time_s = 2.0 * 512.0 / 8000.0;
+    let freq_hz = spec_data.bin_frequencies()[3];
+
+    assert_eq!(spec_data.sample_at(time_s, freq_hz), Some(-12.5));
+    // Slightly off-center coordinates still land on the nearest cell
+    assert_eq!(spec_data.sample_at(time_s + 0.01, freq_hz + 1.0), Some(-12.5));
+    // Out-of-range queries return None
+    assert_eq!(spec_data.sample_at(-1.0, freq_hz), None);
+    assert_eq!(spec_data.sample_at(time_s, 1.0e6), None);
+    assert_eq!(spec_data.sample_at(1.0e6, freq_hz), None);
+}
+
+#[test]
+fn test_instantaneous_frequency_flat_for_constant_tone() {
+    // Complex exponential at +500 Hz: interleaved I/Q pairs
+    let sample_rate = 8000.0f32;
+    let hz = 500.0f32;
+    let mut samples = Vec::with_capacity(2 * 8000);
+    for i in 0..8000 {
+        let phase = 2.0 * std::f32::consts::PI * hz * i as f32 / sample_rate;
+        samples.push(phase.cos() * 0.5);
+        samples.push(phase.sin() * 0.5);
+    }
+    let params = CalcParams {
+        n_fft: 256,
+        hop_length: 64,
+        window_size: 256,
+        compute_phase: true,
+        signal_type: SignalType::Iq,
+        ..Default::default()
+    };
+    let spec_data =
+        calculate_spectrogram_from_samples(&samples, 8000, params, |_, _| {}).unwrap();
+
+    let inst = instantaneous_frequency(&spec_data).unwrap();
+    assert_eq!(inst.data.len(), spec_data.data.len() - 1);
+
+    // The tone bin's deviation stays flat across all frames and near zero,
+    // since 500 Hz sits exactly on a bin center (8000 / 256 * 16)
+    let bin_freqs = spec_data.bin_frequencies();
+    let tone_bin = bin_freqs.iter().position(|&f| (f - hz).abs() < 1.0).unwrap();
+    let devs: Vec<f32> = inst.data.iter().map(|frame| frame[tone_bin]).collect();
+    let spread = devs.iter().cloned().fold(f32::MIN, f32::max)
+        - devs.iter().cloned().fold(f32::MAX, f32::min);
+    assert!(spread < 1.0, "deviation spread {} Hz is not flat", spread);
+    assert!(devs[0].abs() < 1.0, "bin-centered tone must read near zero, got {}", devs[0]);
+}
+
+#[test]
+fn test_max_hold_keeps_brief_burst() {
+    // A tone present in a single frame out of many quiet ones
+    let mut data = vec![vec![-90.0f32; 64]; 20];
+    data[7][20] = -10.0;
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+
+    let held = max_hold(&spec_data);
+    assert_eq!(held.data.len(), 1);
+    assert_eq!(held.data[0][20], -10.0);
+    assert_eq!(held.data[0][21], -90.0);
+
+    // Welch averaging would dilute the burst by ~13 dB (1 of 20 frames)
+    let averaged = welch_average(&spec_data);
+    assert!(averaged.data[0][20] < held.data[0][20] - 10.0);
+}
+
+#[test]
+fn test_temporal_difference_highlights_onset_only() {
+    // Silence for 4 frames, then a steady tone in bin 5
+    let mut data = vec![vec![-80.0f32; 16]; 10];
+    for col in data.iter_mut().skip(4) {
+        col[5] = -10.0;
+    }
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+
+    let diffed = temporal_difference(&spec_data);
+    assert_eq!(diffed.data.len(), 10);
+    // The onset frame carries the full 70 dB rise
+    assert!((diffed.data[4][5] - 70.0).abs() < 1.0e-3);
+    // Steady frames after the onset are dim, and decays never go negative
+    for col in diffed.data.iter().skip(5) {
+        assert!(col.iter().all(|&v| v.abs() < 1.0e-3));
+    }
+    // The first frame has no predecessor
+    assert!(diffed.data[0].iter().all(|&v| v == 0.0));
+}
+
+#[test]
+fn test_gaussian_window_sigma_controls_support() {
+    let wide = gaussian_window(256, 0.5);
+    let narrow = gaussian_window(256, 0.2);
+
+    // Both peak at 1.0 in the center
+    assert!((wide[127] - 1.0).abs() < 1.0e-3);
+    assert!((narrow[127] - 1.0).abs() < 1.0e-3);
+
+    // Smaller sigma narrows the effective support: fewer samples above half power
+    let support = |w: &[f32]| w.iter().filter(|&&v| v > 0.5).count();
+    assert!(support(&narrow) < support(&wide));
+    // And the edges fall off much harder
+    assert!(narrow[0] < wide[0]);
+    assert!(narrow[0] < 1.0e-4);
+}
+
+#[test]
+fn test_rectangular_window_is_all_ones_and_leaks_more_than_hann() {
+    let window = rectangular_window(64);
+    assert_eq!(window.len(), 64);
+    assert!(window.iter().all(|&v| v == 1.0));
+
+    // An off-bin-center tone: the rectangular window's sidelobes (-13 dB)
+    // leak far more than Hann's (-31 dB)
+    let n_fft = 1024usize;
+    let sample_rate = 8000.0;
+    let freq = 32.5 * sample_rate / n_fft as f32;
+    let path = std::env::temp_dir().join("sgvr_test_rect.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / sample_rate;
+        let sample = (2.0 * std::f32::consts::PI * freq * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let leakage = |window_type: WindowType| {
+        let params = CalcParams {
+            n_fft,
+            window_size: n_fft,
+            hop_length: 512,
+            window_type,
+            ..Default::default()
+        };
+        let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+        let col = &spec_data.data[0];
+        let peak = col.iter().cloned().fold(f32::MIN, f32::max);
+        // Level 8 bins above the tone, relative to the peak
+        col[40] - peak
+    };
+
+    let rect = leakage(WindowType::Rectangular);
+    let hann = leakage(WindowType::Hann);
+    assert!(rect > hann + 10.0, "rect {} dB vs hann {} dB", rect, hann);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_nuttall_window_suppresses_sidelobes_below_hann() {
+    // Bin-centered tone so all off-peak energy is window sidelobes
+    let n_fft = 1024usize;
+    let sample_rate = 8000.0;
+    let freq = 32.0 * sample_rate / n_fft as f32;
+    let path = std::env::temp_dir().join("sgvr_test_nuttall.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / sample_rate;
+        let sample = (2.0 * std::f32::consts::PI * freq * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // Highest level outside the window's own main lobe, relative to the
+    // peak; the main-lobe half-width differs per window
+    let sidelobe = |window_type: WindowType, lobe_halfwidth: usize| {
+        let params = CalcParams {
+            n_fft,
+            window_size: n_fft,
+            hop_length: 512,
+            window_type,
+            ..Default::default()
+        };
+        let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+        let col = &spec_data.data[0];
+        let peak = col.iter().cloned().fold(f32::MIN, f32::max);
+        col[(32 + lobe_halfwidth)..64].iter().cloned().fold(f32::MIN, f32::max) - peak
+    };
+
+    let nuttall = sidelobe(WindowType::Nuttall, 5);
+    let hann = sidelobe(WindowType::Hann, 3);
+    assert!(nuttall < hann - 20.0, "nuttall {} dB vs hann {} dB", nuttall, hann);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_measure_tone_finds_880_hz_with_high_snr() {
+    let path = std::env::temp_dir().join("sgvr_test_measure.wav");
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for t in 0..8000 {
+        let time = t as f32 / 8000.0;
+        let sample = (2.0 * std::f32::consts::PI * 880.0 * time).sin() * 0.5;
+        writer.write_sample((sample * i16::MAX as f32) as i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let params = CalcParams {
+        n_fft: 1024,
+        window_size: 1024,
+        hop_length: 512,
+        ..Default::default()
+    };
+    let spec_data = calculate_spectrogram(&path, params, |_, _| {}).unwrap();
+    let m = measure_tone(&spec_data, 880.0).unwrap();
+
+    // 880 Hz at 8 kHz / 1024 bins lands nearest bin 113 (882.8 Hz)
+    assert_eq!(m.bin, 113);
+    assert!((m.freq_hz - 880.0).abs() < 8000.0 / 1024.0);
+    assert!(m.snr_db > 40.0, "SNR {} dB", m.snr_db);
+
+    std::fs::remove_file(&path).ok();
+}
@@ -1,6 +1,8 @@
-use super::scalc::SpectrogramData;
+use super::scalc::{peak_bins, ChirpFit, SpectrogramData};
 use image::{Rgb, RgbImage};
 use hsl::HSL;
+use rayon::prelude::*;
+use std::sync::{Mutex, OnceLock};
 
 /// RGB color structure for gradients and colormaps
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,7 +24,7 @@ impl Color {
 }
 
 /// Supported color schemes for spectrogram rendering
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ColorScheme {
     Oceanic,   // linear-gradient(to right, #01041B, #072e69, #4da4d5, #dcf3ff)
     Grayscale, // linear-gradient(to right, #000000, #888888, #ffffff)
@@ -30,6 +32,9 @@ pub enum ColorScheme {
     Viridis,   // linear-gradient(to right, #440154, #3b528b, #21918c, #5ec962, #fde725)
     Synthwave, // linear-gradient(to right, #0d0221, #2d134b, #a537fd, #00f6ff)
     Sunset,    // linear-gradient(to right, #3c031c, #9c1521, #fd6a02, #fec812)
+    Diverging, // linear-gradient(to right, #2166ac, #ffffff, #b2182b), white at the midpoint
+    /// User-supplied gradient stops (at least two)
+    Custom(Vec<Color>),
 }
 
 const OCEANIC: [Color; 4] = [
@@ -75,7 +80,13 @@ const SUNSET: [Color; 4] = [
     Color::new_rgb(0xfec812),
 ];
 
-pub fn get_color_stops(scheme: ColorScheme) -> &'static [Color] {
+const DIVERGING: [Color; 3] = [
+    Color::new_rgb(0x2166ac),
+    Color::new_rgb(0xffffff),
+    Color::new_rgb(0xb2182b),
+];
+
+pub fn get_color_stops(scheme: &ColorScheme) -> &[Color] {
     match scheme {
         ColorScheme::Oceanic   => &OCEANIC,
         ColorScheme::Grayscale => &GRAYSCALE,
@@ -83,26 +94,587 @@ pub fn get_color_stops(scheme: ColorScheme) -> &'static [Color] {
         ColorScheme::Viridis   => &VIRIDIS,
         ColorScheme::Synthwave => &SYNTHWAVE,
         ColorScheme::Sunset    => &SUNSET,
+        ColorScheme::Diverging => &DIVERGING,
+        ColorScheme::Custom(stops) => stops,
+    }
+}
+
+/// Vertical (frequency) axis scale
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FreqScale {
+    /// Pixel rows map to bins linearly
+    Linear,
+    /// Pixel rows map to bins geometrically, giving low bands more space
+    Log,
+}
+
+/// Which image axis time runs along
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    /// Time left-to-right, frequency bottom-to-top (the default)
+    TimeX,
+    /// Waterfall style: time top-to-bottom, frequency left-to-right
+    TimeY,
+}
+
+/// How master columns covered by one pixel column are reduced to a single value
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColumnReduce {
+    /// Keep the maximum (preserves peaks and short events)
+    Max,
+    /// Arithmetic mean of the dB values (smoother, de-emphasizes noise)
+    Mean,
+    /// Keep the minimum
+    Min,
+}
+
+/// How the top of the dB range is chosen for color normalization
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Normalization {
+    /// Use the global maximum dB value (a single hot pixel sets the range)
+    Peak,
+    /// Use a percentile of all dB values, so isolated spikes don't wash
+    /// out the rest of the image
+    Percentile,
+}
+
+/// Параметры рендеринга спектрограммы
+#[derive(Debug, Clone)]
+pub struct RenderParams {
+    pub width: u32,
+    pub height: u32,
+    pub color_scheme: ColorScheme,
+    pub dynamic_range: f32,
+    /// Ignore `dynamic_range` and derive the display range from the data:
+    /// the bottom is the estimated noise floor (median dB) minus a small
+    /// headroom, the top is the normalization maximum as usual
+    pub auto_range: bool,
+    /// Render bin 0 (DC) at the top of the image instead of the bottom
+    pub freq_top: bool,
+    /// Lowest frequency (Hz) mapped into the image; bins below are cropped
+    pub freq_min: Option<f32>,
+    /// Highest frequency (Hz) mapped into the image; bins above are cropped
+    pub freq_max: Option<f32>,
+    /// Linear or logarithmic frequency axis
+    pub freq_scale: FreqScale,
+    /// Reverse the gradient (dark-on-light output, e.g. for printing)
+    pub invert_colormap: bool,
+    /// Color space the gradient stops are interpolated in
+    pub interp_space: InterpSpace,
+    /// Axis orientation; `TimeY` swaps the output dimensions.
+    /// Labeled axes are only drawn in the `TimeX` orientation.
+    pub orientation: Orientation,
+    /// How multiple master columns collapse into one pixel column
+    pub reduce: ColumnReduce,
+    /// Blend adjacent frequency bins bilinearly instead of nearest-neighbor
+    /// (smoother upscaling at some extra cost)
+    pub interpolate: bool,
+    /// Gamma applied to the normalized value before color lookup;
+    /// values above 1.0 brighten weak detail
+    pub gamma: f32,
+    /// Peak or percentile-based dB normalization
+    pub normalize: Normalization,
+    /// Percentile used when `normalize` is `Percentile` (e.g. 99.0)
+    pub percentile: f32,
+    /// Draw labeled frequency/time axes in a margin around the spectrogram
+    pub axes: bool,
+    /// Hop length used for the calculation, needed to label the time axis
+    pub hop_length: usize,
+    /// Map 0 to the center of the gradient, spreading positive and negative
+    /// values outward symmetrically (for difference spectrograms)
+    pub diverging: bool,
+    /// Draw faint horizontal markers at this many of the strongest
+    /// time-averaged bins, labeled with their frequency when `axes` is on
+    pub mark_peaks: Option<usize>,
+    /// Absolute dB threshold: anything below maps straight to the gradient's
+    /// bottom color regardless of the dynamic range, hiding sub-noise pixels
+    pub floor_db: Option<f32>,
+    /// Draw this fitted chirp as a faint line over the spectrogram
+    pub chirp_overlay: Option<ChirpFit>,
+    /// Paint near-peak cells of clipped frames in this color, so saturated
+    /// input stands out from loud-but-clean signal
+    pub clip_color: Option<Color>,
+    /// Caption drawn in a bar above the spectrogram (e.g. filename or date);
+    /// the image grows by the bar height
+    pub title: Option<String>,
+}
+
+impl Default for RenderParams {
+    fn default() -> Self {
+        Self {
+            width: 2048,
+            height: 512,
+            color_scheme: ColorScheme::Oceanic,
+            dynamic_range: 110.0,
+            auto_range: false,
+            freq_top: false,
+            freq_min: None,
+            freq_max: None,
+            freq_scale: FreqScale::Linear,
+            invert_colormap: false,
+            interp_space: InterpSpace::Hsl,
+            orientation: Orientation::TimeX,
+            reduce: ColumnReduce::Max,
+            interpolate: false,
+            gamma: 1.0,
+            normalize: Normalization::Peak,
+            percentile: 99.0,
+            axes: false,
+            hop_length: 512,
+            diverging: false,
+            mark_peaks: None,
+            floor_db: None,
+            chirp_overlay: None,
+            clip_color: None,
+            title: None,
+        }
+    }
+}
+
+/// Translate the optional Hz crop bounds into a half-open bin range over
+/// the master data, clamped so at least one bin always remains
+fn crop_range(spec_data: &SpectrogramData, params: &RenderParams) -> (usize, usize) {
+    let master_height = spec_data.data.first().map_or(0, |col| col.len());
+    if master_height == 0 {
+        return (0, 0);
+    }
+    // Bin centers are ascending for both real and I/Q layouts
+    let bin_freqs = spec_data.bin_frequencies();
+    let lo = params.freq_min
+        .map(|hz| bin_freqs.partition_point(|&f| f < hz))
+        .unwrap_or(0)
+        .min(master_height - 1);
+    let hi = params.freq_max
+        .map(|hz| bin_freqs.partition_point(|&f| f <= hz))
+        .unwrap_or(master_height)
+        .clamp(lo + 1, master_height);
+    (lo, hi)
+}
+
+/// Map an image row (counted from the DC side) to a master data bin index
+fn row_to_bin(row: u32, height: u32, master_height: usize, freq_scale: FreqScale) -> usize {
+    match freq_scale {
+        FreqScale::Linear => (row as usize * master_height) / height as usize,
+        FreqScale::Log => {
+            // Geometric mapping from bin 1 (DC has no log position) up to the top bin
+            let max_bin = (master_height - 1).max(1) as f32;
+            let fraction = row as f32 / (height - 1).max(1) as f32;
+            (max_bin.powf(fraction).round() as usize).min(master_height - 1)
+        }
+    }
+}
+
+/// Fractional bin position of an image row, for bilinear interpolation
+fn row_to_bin_fractional(row: u32, height: u32, master_height: usize, freq_scale: FreqScale) -> f32 {
+    let fraction = row as f32 / (height - 1).max(1) as f32;
+    match freq_scale {
+        FreqScale::Linear => fraction * (master_height - 1) as f32,
+        FreqScale::Log => ((master_height - 1).max(1) as f32).powf(fraction),
     }
 }
 
-/// Create a spectrogram image from data, with given size, color scheme, and dynamic range (dB)
+/// Vertical gap between the two panes of the dual I/Q render, px
+const DUAL_PANE_GAP: u32 = 2;
+
+/// Render magnitude and instantaneous frequency as two stacked panels
+/// sharing the time axis: magnitude on top with the configured scheme,
+/// the signed frequency deviation below on the zero-centered diverging map
+pub fn render_iq_dual(
+    mag: &SpectrogramData,
+    inst: &SpectrogramData,
+    params: &RenderParams,
+) -> RgbImage {
+    let top = render_spectrogram(mag, params);
+
+    let bottom_params = RenderParams {
+        color_scheme: ColorScheme::Diverging,
+        diverging: true,
+        floor_db: None,
+        clip_color: None,
+        chirp_overlay: None,
+        mark_peaks: None,
+        ..params.clone()
+    };
+    let bottom = render_spectrogram(inst, &bottom_params);
+
+    let mut img = RgbImage::new(params.width, params.height * 2 + DUAL_PANE_GAP);
+    for (x, y, pixel) in top.enumerate_pixels() {
+        img.put_pixel(x, y, *pixel);
+    }
+    for (x, y, pixel) in bottom.enumerate_pixels() {
+        img.put_pixel(x, y + params.height + DUAL_PANE_GAP, *pixel);
+    }
+    img
+}
+
+/// Render the I and Q channels (each analyzed as a real signal) as two
+/// stacked panels sharing one dB normalization, so a channel imbalance
+/// shows up directly as a brightness difference between the panels
+pub fn render_iq_split(
+    i_data: &SpectrogramData,
+    q_data: &SpectrogramData,
+    params: &RenderParams,
+) -> RgbImage {
+    // Shared range: the louder channel's peak, keeping the wider span
+    let i_range = display_range(i_data, params);
+    let q_range = display_range(q_data, params);
+    let span = (i_range.max_db - i_range.min_db).max(q_range.max_db - q_range.min_db);
+    let max_db = i_range.max_db.max(q_range.max_db);
+    let shared = DisplayRange {
+        min_db: max_db - span,
+        max_db,
+        max_abs: i_range.max_abs.max(q_range.max_abs),
+    };
+
+    let top = render_with_range(i_data, params, &shared);
+    let bottom = render_with_range(q_data, params, &shared);
+
+    let mut img = RgbImage::new(params.width, params.height * 2 + DUAL_PANE_GAP);
+    for (x, y, pixel) in top.enumerate_pixels() {
+        img.put_pixel(x, y, *pixel);
+    }
+    for (x, y, pixel) in bottom.enumerate_pixels() {
+        img.put_pixel(x, y + params.height + DUAL_PANE_GAP, *pixel);
+    }
+    img
+}
+
+/// Map a pixel of the plain (axis-free, `TimeX`) spectrogram back to its
+/// data coordinates: `(time_s, freq_hz, dB)`
+///
+/// The inverse of the render mapping: honors the frequency crop, the
+/// linear/log scale and `freq_top`. Returns `None` when the pixel lies
+/// outside the image or the data is empty.
+#[allow(dead_code)] // Library-style API, exercised by tests
+pub fn pixel_to_data_coords(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+    x: u32,
+    y: u32,
+) -> Option<(f32, f32, f32)> {
+    if x >= params.width || y >= params.height || spec_data.data.is_empty() {
+        return None;
+    }
+    let master_width = spec_data.data.len();
+    let (crop_lo, crop_hi) = crop_range(spec_data, params);
+    let cropped_height = crop_hi - crop_lo;
+
+    let col = ((x as usize * master_width) / params.width as usize).min(master_width - 1);
+    let row = if params.freq_top { y } else { params.height - 1 - y };
+    let bin = crop_lo + row_to_bin(row, params.height, cropped_height, params.freq_scale);
+    let bin = bin.min(crop_hi - 1);
+
+    let time_s = col as f32 * spec_data.hop_length as f32 / spec_data.sample_rate as f32;
+    let freq_hz = spec_data.bin_frequencies()[bin];
+    let db = *spec_data.data[col].get(bin)?;
+    Some((time_s, freq_hz, db))
+}
+
+/// Create a spectrogram image from data according to the rendering parameters
 ///
 /// - `spec_data`: Spectrogram data (matrix of dB values)
-/// - `width`, `height`: Output image size in pixels
-/// - `color_scheme`: Color scheme for rendering
-/// - `dynamic_range`: Dynamic range in dB (e.g., 110.0)
+/// - `params`: Rendering parameters (size, color scheme, dynamic range, axis orientation)
 ///
-/// Returns: RGB image
+/// Returns: RGB image; with `axes` enabled the spectrogram is inset into a
+/// labeled margin and the image grows by the margin size
 pub fn create_spectrogram_image(
     spec_data: &SpectrogramData,
-    width: u32,
-    height: u32,
-    color_scheme: ColorScheme,
-    dynamic_range: f32,
+    params: &RenderParams,
 ) -> RgbImage {
-    let color_stops = get_color_stops(color_scheme);
-    let gradient = generate_gradient_hsl(color_stops);
+    let mut plain = render_spectrogram(spec_data, params);
+    for (y, _) in peak_marker_rows(spec_data, params) {
+        // Faint line: blend a bit of white over the spectrogram pixels
+        for x in 0..plain.width() {
+            let Rgb([r, g, b]) = *plain.get_pixel(x, y);
+            let lift = |c: u8| (c as u16 * 7 / 10 + 255 * 3 / 10) as u8;
+            plain.put_pixel(x, y, Rgb([lift(r), lift(g), lift(b)]));
+        }
+    }
+    if let Some(fit) = &params.chirp_overlay {
+        draw_chirp_overlay(spec_data, params, &mut plain, fit);
+    }
+    let composed = match params.orientation {
+        Orientation::TimeX if params.axes => compose_with_axes(spec_data, params, &plain),
+        Orientation::TimeX => plain,
+        Orientation::TimeY => rotate_for_time_y(&plain),
+    };
+    match &params.title {
+        Some(title) => compose_with_caption(title, &composed),
+        None => composed,
+    }
+}
+
+/// Blend the fitted chirp line over the plain spectrogram
+///
+/// Each pixel column maps its frame time through the fit to a ridge
+/// frequency and back to an image row; rows between neighboring columns are
+/// filled so steep sweeps stay connected. Columns whose fitted frequency
+/// falls outside the cropped range are skipped.
+fn draw_chirp_overlay(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+    plain: &mut RgbImage,
+    fit: &ChirpFit,
+) {
+    if spec_data.data.is_empty() {
+        return;
+    }
+    let (crop_lo, crop_hi) = crop_range(spec_data, params);
+    let cropped_height = crop_hi - crop_lo;
+    let bin_freqs = spec_data.bin_frequencies();
+    let frame_times = spec_data.frame_times(spec_data.hop_length);
+    let master_width = spec_data.data.len();
+    let t_start = frame_times[0];
+
+    let mut prev_y: Option<u32> = None;
+    for x in 0..params.width {
+        let col = (x as usize * master_width) / params.width as usize;
+        let t = frame_times[col.min(master_width - 1)];
+        let hz = fit.start_hz + fit.rate_hz_per_s * (t - t_start);
+        let bin = bin_freqs.partition_point(|&f| f < hz);
+        if !(crop_lo..crop_hi).contains(&bin) {
+            prev_y = None;
+            continue;
+        }
+        // Nearest row of the target bin; with fewer rows than bins the
+        // closest one still keeps the line continuous
+        let Some(row) = (0..params.height).min_by_key(|&row| {
+            (crop_lo + row_to_bin(row, params.height, cropped_height, params.freq_scale))
+                .abs_diff(bin)
+        }) else {
+            prev_y = None;
+            continue;
+        };
+        let y = if params.freq_top { row } else { params.height - 1 - row };
+        let (lo, hi) = match prev_y {
+            Some(p) => (p.min(y), p.max(y)),
+            None => (y, y),
+        };
+        for yy in lo..=hi {
+            let Rgb([r, g, b]) = *plain.get_pixel(x, yy);
+            let lift = |c: u8| (c as u16 * 7 / 10 + 255 * 3 / 10) as u8;
+            plain.put_pixel(x, yy, Rgb([lift(r), lift(g), lift(b)]));
+        }
+        prev_y = Some(y);
+    }
+}
+
+/// Render straight to raw RGB bytes plus dimensions, for handing to a GUI
+/// texture without any file round trip
+///
+/// The bytes are tightly packed rows of `R, G, B` triples, top row first —
+/// the layout GPU texture uploads expect. Returns `(bytes, width, height)`;
+/// with `axes` enabled the dimensions include the label margins.
+#[allow(dead_code)] // Library-style entry point, exercised by tests
+pub fn render_to_rgb_bytes(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+) -> (Vec<u8>, u32, u32) {
+    let img = create_spectrogram_image(spec_data, params);
+    let (width, height) = img.dimensions();
+    (img.into_raw(), width, height)
+}
+
+/// Encode a rendered image as PNG into an in-memory buffer, for callers
+/// that want the file bytes without touching the filesystem
+#[allow(dead_code)] // Library-style entry point, exercised by tests
+pub fn encode_png_bytes(img: &RgbImage) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+/// Rotate the rendered image so time runs top-to-bottom and
+/// frequency runs left-to-right (low frequencies on the left)
+fn rotate_for_time_y(img: &RgbImage) -> RgbImage {
+    let (width, height) = img.dimensions();
+    let mut out = RgbImage::new(height, width);
+    for (x, y, pixel) in img.enumerate_pixels() {
+        out.put_pixel(height - 1 - y, x, *pixel);
+    }
+    out
+}
+
+/// Render a long capture as a sequence of fixed-width PNG tiles suitable for
+/// stitching into a scrolling video (e.g. with ffmpeg)
+///
+/// Each tile covers `tile_duration` seconds of signal, is rendered at the
+/// image size from `params` and shares one dB normalization computed over the
+/// whole capture, so brightness stays consistent across the sequence. Tiles
+/// are written to `dir` (created if missing) as `frame_000000.png`,
+/// `frame_000001.png`, ... Returns the number of tiles written.
+pub fn write_frame_tiles(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+    dir: &std::path::Path,
+    tile_duration: f32,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    if tile_duration <= 0.0 {
+        return Err(format!("invalid frame duration: {} (must be positive)", tile_duration).into());
+    }
+    if spec_data.data.is_empty() {
+        return Ok(0);
+    }
+    let hop = params.hop_length.max(1);
+    let cols_per_tile = ((tile_duration * spec_data.sample_rate as f32 / hop as f32)
+        .round() as usize).max(1);
+    let range = display_range(spec_data, params);
+    std::fs::create_dir_all(dir)?;
+
+    let mut written = 0;
+    for (i, chunk) in spec_data.data.chunks(cols_per_tile).enumerate() {
+        let start = i * cols_per_tile;
+        let tile = SpectrogramData {
+            data: chunk.to_vec(),
+            sample_rate: spec_data.sample_rate,
+            phase: None,
+            clipped: spec_data.clipped
+                .get(start..(start + chunk.len()).min(spec_data.clipped.len()))
+                .map(<[bool]>::to_vec)
+                .unwrap_or_default(),
+            signal_type: spec_data.signal_type,
+            hop_length: spec_data.hop_length,
+        };
+        let img = render_with_range(&tile, params, &range);
+        let img = match params.orientation {
+            Orientation::TimeX => img,
+            Orientation::TimeY => rotate_for_time_y(&img),
+        };
+        img.save(dir.join(format!("frame_{:06}.png", i)))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Extra dB shown below the estimated noise floor in auto-range mode
+/// so the floor itself stays visible instead of clipping to black
+const AUTO_RANGE_HEADROOM_DB: f32 = 6.0;
+
+/// dB below the normalization maximum still painted in the clip color
+/// inside clipped frames; quieter cells of those frames render normally
+const CLIP_MARK_RANGE_DB: f32 = 6.0;
+
+/// Image rows (in plain-spectrogram coordinates) of the requested peak
+/// markers, paired with the Hz value of the marked bin for labeling
+///
+/// Peaks outside the cropped frequency range are skipped, as are bins
+/// the image is too small to give their own row.
+fn peak_marker_rows(spec_data: &SpectrogramData, params: &RenderParams) -> Vec<(u32, f32)> {
+    let Some(count) = params.mark_peaks else {
+        return Vec::new();
+    };
+    if spec_data.data.is_empty() {
+        return Vec::new();
+    }
+    let (crop_lo, crop_hi) = crop_range(spec_data, params);
+    let cropped_height = crop_hi - crop_lo;
+    let bin_freqs = spec_data.bin_frequencies();
+
+    peak_bins(spec_data, count)
+        .into_iter()
+        .filter(|bin| (crop_lo..crop_hi).contains(bin))
+        .filter_map(|bin| {
+            let row = (0..params.height).find(|&row| {
+                crop_lo + row_to_bin(row, params.height, cropped_height, params.freq_scale) == bin
+            })?;
+            let y = if params.freq_top { row } else { params.height - 1 - row };
+            Some((y, bin_freqs[bin]))
+        })
+        .collect()
+}
+
+/// Color-normalization bounds, computed once per image — or once per frame
+/// export, so every tile of a sequence shares the same brightness
+struct DisplayRange {
+    min_db: f32,
+    max_db: f32,
+    /// Largest |dB| value, used instead of the min→max range in diverging mode
+    max_abs: f32,
+}
+
+/// Derive the dB→color normalization bounds from the data; assumes
+/// `spec_data.data` is non-empty
+fn display_range(spec_data: &SpectrogramData, params: &RenderParams) -> DisplayRange {
+    // Find the top of the dB range for color normalization: the global max,
+    // or a percentile of all values so outliers don't set the range
+    // NaN or ±inf cells (e.g. a log of an all-zero window) must not poison
+    // the statistics, so every fold below skips non-finite values
+    let max_db = match params.normalize {
+        Normalization::Peak => spec_data.data.iter()
+            .flat_map(|col| col.iter())
+            .filter(|v| v.is_finite())
+            .cloned()
+            .fold(f32::MIN, f32::max),
+        Normalization::Percentile => {
+            let mut values: Vec<f32> = spec_data.data.iter()
+                .flat_map(|col| col.iter())
+                .filter(|v| v.is_finite())
+                .cloned()
+                .collect();
+            if values.is_empty() {
+                f32::MIN
+            } else {
+                values.sort_unstable_by(f32::total_cmp);
+                let idx = ((params.percentile.clamp(0.0, 100.0) / 100.0)
+                    * (values.len() - 1) as f32).round() as usize;
+                values[idx]
+            }
+        }
+    };
+    let min_db = if params.auto_range {
+        // Estimate the noise floor as the median dB across all bins; most
+        // bins of a typical signal are noise, so the median sits on the floor
+        let mut values: Vec<f32> = spec_data.data.iter()
+            .flat_map(|col| col.iter())
+            .filter(|v| v.is_finite())
+            .cloned()
+            .collect();
+        if values.is_empty() {
+            max_db - params.dynamic_range
+        } else {
+            values.sort_unstable_by(f32::total_cmp);
+            values[values.len() / 2] - AUTO_RANGE_HEADROOM_DB
+        }
+    } else {
+        max_db - params.dynamic_range
+    };
+
+    // In diverging mode 0 maps to the gradient center, so normalization uses
+    // the largest absolute value instead of the min→max range
+    let max_abs = if params.diverging {
+        spec_data.data.iter()
+            .flat_map(|col| col.iter())
+            .filter(|v| v.is_finite())
+            .map(|v| v.abs())
+            .fold(0.0f32, f32::max)
+    } else {
+        0.0
+    };
+
+    DisplayRange { min_db, max_db, max_abs }
+}
+
+/// Render the bare spectrogram pixels (no margins or labels)
+fn render_spectrogram(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+) -> RgbImage {
+    if spec_data.data.is_empty() {
+        return RgbImage::new(params.width, params.height);
+    }
+    render_with_range(spec_data, params, &display_range(spec_data, params))
+}
+
+/// Render the bare spectrogram pixels with an externally supplied color
+/// normalization, so tiled exports stay consistent across tiles
+fn render_with_range(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+    range: &DisplayRange,
+) -> RgbImage {
+    let RenderParams { width, height, .. } = *params;
+    let DisplayRange { min_db, max_db, max_abs } = *range;
+    let gradient = cached_gradient(&params.color_scheme, params.invert_colormap, params.interp_space);
 
     let mut img = RgbImage::new(width, height);
 
@@ -110,48 +682,294 @@ pub fn create_spectrogram_image(
         return img;
     }
 
-    let master_width  = spec_data.data.len();     
-    let master_height = spec_data.data[0].len(); 
+    let master_width  = spec_data.data.len();
+    // Frequency cropping narrows the bin range the image rows map onto
+    let (crop_lo, crop_hi) = crop_range(spec_data, params);
+    let cropped_height = crop_hi - crop_lo;
 
-    // Find global min and max dB for color normalization
-    let max_db = spec_data.data.iter()
-        .flat_map(|col| col.iter())
-        .cloned()
-        .fold(f32::MIN, f32::max);
-    let min_db = max_db - dynamic_range;
-
-    for x in 0..width {
+    // Pixel columns are independent, so they are computed in parallel and
+    // blitted sequentially; the active rayon pool bounds the parallelism
+    let columns: Vec<Vec<Rgb<u8>>> = (0..width).into_par_iter().map(|x| {
         // Determine the range of columns in master data covered by this pixel column `x`
         let start_col = (x as usize * master_width) / width as usize;
         let end_col = ((x as usize + 1) * master_width) / width as usize;
 
         let end_col = end_col.max(start_col + 1);
 
-        for y in 0..height {
-            // Scale vertical axis (frequencies) using nearest neighbor interpolation
-            // Invert `y` because (0,0) is top-left in image, but we want low frequencies at the bottom
-            let freq_bin_index = ((height - 1 - y) as usize * master_height) / height as usize;
-
-            // Find MAX value in [start_col, end_col) for this frequency bin 
-            // for preserves peaks and short events
-            let mut max_val = f32::NEG_INFINITY;
-            for i in start_col..end_col {
-                if let Some(col) = spec_data.data.get(i) {
-                    if let Some(val) = col.get(freq_bin_index) {
-                        if *val > max_val {
-                            max_val = *val;
+        // Does this pixel column cover a frame flagged as clipped?
+        let column_clipped = params.clip_color.is_some()
+            && spec_data.clipped.iter().take(end_col).skip(start_col).any(|&c| c);
+
+        // Reduce the values in [start_col, end_col) for one frequency bin;
+        // MAX (the default) preserves peaks and short events
+        let reduce_bin = |freq_bin_index: usize| {
+            let mut extreme = match params.reduce {
+                ColumnReduce::Min => f32::INFINITY,
+                _ => f32::NEG_INFINITY,
+            };
+            let mut sum = 0.0f32;
+            let mut count = 0usize;
+            for col in spec_data.data.iter().take(end_col).skip(start_col) {
+                if let Some(val) = col.get(freq_bin_index) {
+                    match params.reduce {
+                        ColumnReduce::Max => extreme = extreme.max(*val),
+                        ColumnReduce::Min => extreme = extreme.min(*val),
+                        ColumnReduce::Mean => {
+                            sum += *val;
+                            count += 1;
                         }
                     }
                 }
             }
+            match params.reduce {
+                ColumnReduce::Mean if count > 0 => sum / count as f32,
+                ColumnReduce::Mean => f32::NEG_INFINITY,
+                _ => extreme,
+            }
+        };
 
-            // Normalize value and map to color using the selected gradient
-            let normalized_val = (max_val - min_db) / (max_db - min_db);
-            let idx = (normalized_val.clamp(0.0, 1.0) * (GRADIENT_SIZE as f32 - 1.0)).round() as usize;
+        (0..height).map(|y| {
+            // Scale vertical axis (frequencies) using nearest neighbor interpolation
+            // By default invert `y` because (0,0) is top-left in image, but we want low
+            // frequencies at the bottom; with `freq_top` bin 0 is rendered at the top
+            let row = if params.freq_top { y } else { height - 1 - y };
+
+            let max_val = if params.interpolate {
+                // Blend the two bins straddling the fractional position
+                let pos = row_to_bin_fractional(row, height, cropped_height, params.freq_scale);
+                let lo = (pos.floor() as usize).min(cropped_height - 1);
+                let hi = (lo + 1).min(cropped_height - 1);
+                let frac = pos - lo as f32;
+                reduce_bin(crop_lo + lo) * (1.0 - frac) + reduce_bin(crop_lo + hi) * frac
+            } else {
+                reduce_bin(crop_lo + row_to_bin(row, height, cropped_height, params.freq_scale))
+            };
+
+            if let Some(clip) = params.clip_color
+                && column_clipped
+                && max_val >= max_db - CLIP_MARK_RANGE_DB
+            {
+                return Rgb([clip.r, clip.g, clip.b]);
+            }
+
+            // Hard floor: sub-threshold (or non-finite) values go straight
+            // to the bottom color for a clean dark background
+            if !max_val.is_finite() || params.floor_db.is_some_and(|floor| max_val < floor) {
+                let c = gradient[0];
+                return Rgb([c.r, c.g, c.b]);
+            }
+
+            // Normalize value and map to color using the selected gradient;
+            // a degenerate range (e.g. a silent file where every cell sits on
+            // the magnitude floor) collapses to a single color instead of NaN
+            let normalized_val = if params.diverging {
+                if max_abs > 0.0 { 0.5 + max_val / (2.0 * max_abs) } else { 0.5 }
+            } else if max_db > min_db {
+                (max_val - min_db) / (max_db - min_db)
+            } else {
+                0.0
+            };
+            // Gamma curve: gamma > 1 lifts the lower (faint) part of the range
+            let curved_val = normalized_val.clamp(0.0, 1.0).powf(1.0 / params.gamma);
+            let idx = (curved_val * (GRADIENT_SIZE as f32 - 1.0)).round() as usize;
             let idx = idx.min(GRADIENT_SIZE - 1);
             let c = gradient[idx];
-            img.put_pixel(x, y, Rgb([c.r, c.g, c.b]));
+            Rgb([c.r, c.g, c.b])
+        }).collect()
+    }).collect();
+
+    for (x, column) in columns.iter().enumerate() {
+        for (y, pixel) in column.iter().enumerate() {
+            img.put_pixel(x as u32, y as u32, *pixel);
+        }
+    }
+
+    img
+}
+
+/// Margin reserved for the frequency labels on the left, px
+pub const AXIS_MARGIN_LEFT: u32 = 44;
+/// Margin reserved for the time labels at the bottom, px
+pub const AXIS_MARGIN_BOTTOM: u32 = 14;
+/// Length of the tick marks, px
+const TICK_LENGTH: u32 = 3;
+
+const FONT_WIDTH: u32 = 5;
+const FONT_HEIGHT: u32 = 7;
+
+/// 5x7 bitmap glyphs for the axis labels (one byte per row, low 5 bits used),
+/// embedded so rendering needs no font dependency
+fn glyph(c: char) -> Option<[u8; 7]> {
+    match c {
+        '0' => Some([0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+        '1' => Some([0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+        '2' => Some([0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+        '3' => Some([0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+        '4' => Some([0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+        '5' => Some([0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+        '6' => Some([0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+        '7' => Some([0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+        '8' => Some([0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+        '9' => Some([0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+        '.' => Some([0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C]),
+        '-' => Some([0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00]),
+        'k' => Some([0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12]),
+        's' => Some([0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E]),
+        'A' => Some([0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+        'B' => Some([0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+        'C' => Some([0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+        'D' => Some([0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E]),
+        'E' => Some([0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+        'F' => Some([0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+        'G' => Some([0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F]),
+        'H' => Some([0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+        'I' => Some([0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+        'J' => Some([0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C]),
+        'K' => Some([0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+        'L' => Some([0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F]),
+        'M' => Some([0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11]),
+        'N' => Some([0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x11]),
+        'O' => Some([0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+        'P' => Some([0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10]),
+        'Q' => Some([0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D]),
+        'R' => Some([0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11]),
+        'S' => Some([0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E]),
+        'T' => Some([0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+        'U' => Some([0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+        'V' => Some([0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04]),
+        'W' => Some([0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A]),
+        'X' => Some([0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11]),
+        'Y' => Some([0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04]),
+        'Z' => Some([0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F]),
+        ':' => Some([0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00]),
+        '/' => Some([0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10]),
+        '_' => Some([0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F]),
+        _ => None,
+    }
+}
+
+/// Vertical padding around the caption text, px
+const CAPTION_PADDING: u32 = 3;
+/// Height of the caption bar added above the image by `title`
+pub const CAPTION_HEIGHT: u32 = FONT_HEIGHT + 2 * CAPTION_PADDING;
+
+/// Put the rendered image under a black caption bar with the title text
+///
+/// The embedded font only carries uppercase letters, so the caption is
+/// uppercased; characters it still cannot draw are skipped.
+fn compose_with_caption(title: &str, base: &RgbImage) -> RgbImage {
+    let mut img = RgbImage::new(base.width(), base.height() + CAPTION_HEIGHT);
+    for (x, y, pixel) in base.enumerate_pixels() {
+        img.put_pixel(x, y + CAPTION_HEIGHT, *pixel);
+    }
+    draw_text(&mut img, CAPTION_PADDING, CAPTION_PADDING, &title.to_uppercase(), Rgb([220, 220, 220]));
+    img
+}
+
+/// Draw a text string with the embedded 5x7 font; unknown characters are skipped
+fn draw_text(img: &mut RgbImage, x: u32, y: u32, text: &str, color: Rgb<u8>) {
+    let mut cursor = x;
+    for c in text.chars() {
+        if let Some(rows) = glyph(c) {
+            for (dy, row_bits) in rows.iter().enumerate() {
+                for dx in 0..FONT_WIDTH {
+                    if row_bits & (1 << (FONT_WIDTH - 1 - dx)) != 0 {
+                        let (px, py) = (cursor + dx, y + dy as u32);
+                        if px < img.width() && py < img.height() {
+                            img.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+        cursor += FONT_WIDTH + 1;
+    }
+}
+
+/// Compact frequency label: Hz below 1 kHz, one decimal of kHz above
+fn format_freq(hz: f32) -> String {
+    if hz >= 1000.0 {
+        format!("{:.1}k", hz / 1000.0)
+    } else {
+        format!("{:.0}", hz)
+    }
+}
+
+/// Compact time label in seconds
+fn format_time(sec: f32) -> String {
+    if sec >= 10.0 {
+        format!("{:.0}s", sec)
+    } else {
+        format!("{:.1}s", sec)
+    }
+}
+
+/// Number of tick marks per axis
+const AXIS_TICKS: u32 = 5;
+
+/// Inset the rendered spectrogram into a margin with frequency and time ticks
+fn compose_with_axes(
+    spec_data: &SpectrogramData,
+    params: &RenderParams,
+    plain: &RgbImage,
+) -> RgbImage {
+    let RenderParams { width, height, .. } = *params;
+    let mut img = RgbImage::new(width + AXIS_MARGIN_LEFT, height + AXIS_MARGIN_BOTTOM);
+
+    // Copy the spectrogram into the inset region
+    for (x, y, pixel) in plain.enumerate_pixels() {
+        img.put_pixel(x + AXIS_MARGIN_LEFT, y, *pixel);
+    }
+
+    if spec_data.data.is_empty() {
+        return img;
+    }
+    let master_width = spec_data.data.len();
+    let label_color = Rgb([220u8, 220, 220]);
+
+    // Frequency ticks on the left, following the same row→bin mapping
+    // (crop included) as the renderer; bin centers come from the data
+    // itself so real and I/Q layouts are labeled consistently
+    let bin_freqs = spec_data.bin_frequencies();
+    let (crop_lo, crop_hi) = crop_range(spec_data, params);
+    let cropped_height = crop_hi - crop_lo;
+    for t in 0..AXIS_TICKS {
+        let y = t * (height - 1) / (AXIS_TICKS - 1);
+        let row = if params.freq_top { y } else { height - 1 - y };
+        let bin = crop_lo + row_to_bin(row, height, cropped_height, params.freq_scale);
+        let hz = bin_freqs[bin.min(crop_hi - 1)];
+
+        for dx in 0..TICK_LENGTH {
+            img.put_pixel(AXIS_MARGIN_LEFT - 1 - dx, y, label_color);
         }
+        let label_y = y.min(height.saturating_sub(FONT_HEIGHT))
+            .saturating_sub(if y > 0 { FONT_HEIGHT / 2 } else { 0 });
+        draw_text(&mut img, 2, label_y, &format_freq(hz), label_color);
+    }
+
+    // Time ticks along the bottom, labeled with the frame time of the
+    // master column each tick lands on (same column mapping as the renderer)
+    let frame_times = spec_data.frame_times(params.hop_length);
+    for t in 0..AXIS_TICKS {
+        let x = t * (width - 1) / (AXIS_TICKS - 1);
+        let col = (x as usize * master_width) / width as usize;
+        let seconds = frame_times[col.min(master_width - 1)];
+
+        for dy in 0..TICK_LENGTH {
+            img.put_pixel(AXIS_MARGIN_LEFT + x, height + dy, label_color);
+        }
+        let label = format_time(seconds);
+        let label_width = label.chars().count() as u32 * (FONT_WIDTH + 1);
+        let label_x = (AXIS_MARGIN_LEFT + x)
+            .saturating_sub(if t + 1 == AXIS_TICKS { label_width } else { label_width / 2 })
+            .max(AXIS_MARGIN_LEFT);
+        draw_text(&mut img, label_x, height + TICK_LENGTH + 1, &label, label_color);
+    }
+
+    // Peak markers get their frequency written right on the marker line
+    for (y, hz) in peak_marker_rows(spec_data, params) {
+        let label_y = y.min(height.saturating_sub(FONT_HEIGHT + 1)).saturating_sub(1);
+        draw_text(&mut img, AXIS_MARGIN_LEFT + 2, label_y, &format_freq(hz), label_color);
     }
 
     img
@@ -159,24 +977,59 @@ pub fn create_spectrogram_image(
 
 const GRADIENT_SIZE: usize = 256;
 
-/// Generate a smooth HSL gradient from a list of color stops
+/// Cache key: scheme, inversion flag, interpolation space
+type GradientKey = (ColorScheme, bool, InterpSpace);
+type GradientTable = [Color; GRADIENT_SIZE];
+
+/// Process-wide cache of generated gradient tables, so batch runs and
+/// frame exports build each `(scheme, invert, space)` table only once
+static GRADIENT_CACHE: OnceLock<Mutex<Vec<(GradientKey, GradientTable)>>> = OnceLock::new();
+
+/// Look up (or generate and remember) the gradient for the given settings;
+/// the reversal for inverted colormaps is baked into the cached table
+fn cached_gradient(scheme: &ColorScheme, invert: bool, space: InterpSpace) -> GradientTable {
+    let cache = GRADIENT_CACHE.get_or_init(|| Mutex::new(Vec::new()));
+    let mut entries = cache.lock().unwrap();
+    if let Some((_, gradient)) = entries.iter()
+        .find(|(key, _)| key.0 == *scheme && key.1 == invert && key.2 == space)
+    {
+        return *gradient;
+    }
+    let mut gradient = generate_gradient(get_color_stops(scheme), space);
+    if invert {
+        gradient.reverse();
+    }
+    entries.push(((scheme.clone(), invert, space), gradient));
+    gradient
+}
+
+/// Color space the gradient stops are interpolated in
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InterpSpace {
+    /// Hue/saturation/lightness with shortest-hue-path logic (the original
+    /// behavior); vivid, but can pass through unexpected hues between
+    /// far-apart stops
+    Hsl,
+    /// Component-wise RGB: predictable, may desaturate in the middle
+    Rgb,
+    /// CIELAB: perceptually even lightness steps
+    Lab,
+}
+
+/// Generate a smooth gradient from a list of color stops, interpolating
+/// in the chosen color space
 ///
 /// - `stops`: Reference colors (at least 2)
 ///
 /// Returns: Array of 256 interpolated Color values
-pub fn generate_gradient_hsl(stops: &[Color]) -> [Color; GRADIENT_SIZE] {
+pub fn generate_gradient(stops: &[Color], space: InterpSpace) -> [Color; GRADIENT_SIZE] {
     if stops.is_empty() { panic!("List of reference colors cannot be empty"); }
     if stops.len() == 1 { return [stops[0]; GRADIENT_SIZE]; }
 
-    // Convert our RGB colors to HSL
-    let hsl_stops: Vec<HSL> = stops.iter()
-        .map(|c| HSL::from_rgb(&[c.r, c.g, c.b]))
-        .collect();
-
     let mut gradient = [Color::new(0, 0, 0); GRADIENT_SIZE];
-    let num_segments = hsl_stops.len() - 1;
+    let num_segments = stops.len() - 1;
 
-    for i in 0..GRADIENT_SIZE {
+    for (i, slot) in gradient.iter_mut().enumerate() {
         let progress = i as f64 / (GRADIENT_SIZE - 1) as f64;
 
         let (segment_index, segment_progress) = if progress >= 1.0 {
@@ -186,37 +1039,113 @@ pub fn generate_gradient_hsl(stops: &[Color]) -> [Color; GRADIENT_SIZE] {
             (segment_float.floor() as usize, segment_float.fract())
         };
 
-        let start_hsl = hsl_stops[segment_index];
-        let end_hsl = hsl_stops[segment_index + 1];
+        let start = stops[segment_index];
+        let end = stops[segment_index + 1];
+        *slot = match space {
+            InterpSpace::Hsl => lerp_hsl(start, end, segment_progress),
+            InterpSpace::Rgb => lerp_rgb(start, end, segment_progress),
+            InterpSpace::Lab => lerp_lab(start, end, segment_progress),
+        };
+    }
 
-        // Interpolation of H, S, L components
+    gradient
+}
 
-        // S and L are interpolated linearly, as before
-        let s = start_hsl.s + (end_hsl.s - start_hsl.s) * segment_progress;
-        let l = start_hsl.l + (end_hsl.l - start_hsl.l) * segment_progress;
+/// Generate a smooth HSL gradient from a list of color stops
+/// (the historical default, kept for callers that don't pick a space)
+#[allow(dead_code)] // Library-style entry point, exercised by tests
+pub fn generate_gradient_hsl(stops: &[Color]) -> [Color; GRADIENT_SIZE] {
+    generate_gradient(stops, InterpSpace::Hsl)
+}
 
-        // For Hue we need special logic for the "short path" around the circle
-        let mut h_start = start_hsl.h;
-        let h_end = end_hsl.h;
-        let h_diff = h_end - h_start;
+/// HSL interpolation with "short path" hue logic around the circle
+fn lerp_hsl(start: Color, end: Color, t: f64) -> Color {
+    let start_hsl = HSL::from_rgb(&[start.r, start.g, start.b]);
+    let end_hsl = HSL::from_rgb(&[end.r, end.g, end.b]);
 
-        if h_diff.abs() > 180.0 {
-            if h_diff > 0.0 {
-                h_start += 360.0;
-            } else {
-                h_start -= 360.0;
-            }
-        }
-        let h = (h_start + (h_end - h_start) * segment_progress) % 360.0;
+    // S and L are interpolated linearly
+    let s = start_hsl.s + (end_hsl.s - start_hsl.s) * t;
+    let l = start_hsl.l + (end_hsl.l - start_hsl.l) * t;
 
-        let new_hsl = HSL { h, s, l };
+    // For Hue we need special logic for the "short path" around the circle
+    let mut h_start = start_hsl.h;
+    let h_end = end_hsl.h;
+    let h_diff = h_end - h_start;
 
-        // Convert the result back to RGB
-        let (r, g, b) = new_hsl.to_rgb();
-        gradient[i] = Color::new(r, g, b);
+    if h_diff.abs() > 180.0 {
+        if h_diff > 0.0 {
+            h_start += 360.0;
+        } else {
+            h_start -= 360.0;
+        }
     }
+    // `%` keeps the sign of its left operand, so normalize into [0, 360)
+    // in case the short-path shift pushed the start below zero
+    let h = (h_start + (h_end - h_start) * t).rem_euclid(360.0);
 
-    gradient
+    let (r, g, b) = (HSL { h, s, l }).to_rgb();
+    Color::new(r, g, b)
+}
+
+/// Plain component-wise interpolation of the 8-bit RGB values
+fn lerp_rgb(start: Color, end: Color, t: f64) -> Color {
+    let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    Color::new(mix(start.r, end.r), mix(start.g, end.g), mix(start.b, end.b))
+}
+
+/// Interpolation in CIELAB (D65), converting back to sRGB
+fn lerp_lab(start: Color, end: Color, t: f64) -> Color {
+    let a = srgb_to_lab(start);
+    let b = srgb_to_lab(end);
+    let mixed = [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ];
+    lab_to_srgb(mixed)
+}
+
+/// D65 reference white in XYZ
+const LAB_WHITE: [f64; 3] = [0.950_47, 1.0, 1.088_83];
+
+fn srgb_to_lab(c: Color) -> [f64; 3] {
+    let lin = |v: u8| {
+        let v = v as f64 / 255.0;
+        if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (lin(c.r), lin(c.g), lin(c.b));
+    let xyz = [
+        0.4124 * r + 0.3576 * g + 0.1805 * b,
+        0.2126 * r + 0.7152 * g + 0.0722 * b,
+        0.0193 * r + 0.1192 * g + 0.9505 * b,
+    ];
+    let f = |v: f64| {
+        if v > 216.0 / 24389.0 { v.cbrt() } else { (24389.0 / 27.0 * v + 16.0) / 116.0 }
+    };
+    let (fx, fy, fz) = (f(xyz[0] / LAB_WHITE[0]), f(xyz[1] / LAB_WHITE[1]), f(xyz[2] / LAB_WHITE[2]));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_srgb(lab: [f64; 3]) -> Color {
+    let fy = (lab[0] + 16.0) / 116.0;
+    let fx = fy + lab[1] / 500.0;
+    let fz = fy - lab[2] / 200.0;
+    let f_inv = |f: f64| {
+        let cubed = f * f * f;
+        if cubed > 216.0 / 24389.0 { cubed } else { (116.0 * f - 16.0) * 27.0 / 24389.0 }
+    };
+    let xyz = [f_inv(fx) * LAB_WHITE[0], f_inv(fy) * LAB_WHITE[1], f_inv(fz) * LAB_WHITE[2]];
+    let (x, y, z) = (xyz[0], xyz[1], xyz[2]);
+    let rgb_lin = [
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    ];
+    let enc = |v: f64| {
+        let v = if v <= 0.003_130_8 { 12.92 * v } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 };
+        (v.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+    Color::new(enc(rgb_lin[0]), enc(rgb_lin[1]), enc(rgb_lin[2]))
 }
 
 #[cfg(test)]
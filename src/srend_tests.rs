@@ -1,5 +1,6 @@
 #[allow(unused_imports)]
 use super::*;
+use crate::scalc::SignalType;
 
 #[test]
 fn test_color_new() {
@@ -35,11 +36,11 @@ fn test_color_scheme_equality() {
 
 #[test]
 fn test_get_color_stops() {
-    let oceanic_stops = get_color_stops(ColorScheme::Oceanic);
+    let oceanic_stops = get_color_stops(&ColorScheme::Oceanic);
     assert_eq!(oceanic_stops.len(), 4);
     assert_eq!(oceanic_stops[0], Color::new_rgb(0x01041B));
     
-    let grayscale_stops = get_color_stops(ColorScheme::Grayscale);
+    let grayscale_stops = get_color_stops(&ColorScheme::Grayscale);
     assert_eq!(grayscale_stops.len(), 3);
     assert_eq!(grayscale_stops[0], Color::new_rgb(0x000000));
     assert_eq!(grayscale_stops[2], Color::new_rgb(0xffffff));
@@ -74,9 +75,16 @@ fn test_generate_gradient_hsl_empty_stops() {
 
 #[test]
 fn test_create_spectrogram_image_empty_data() {
-    let spec_data = SpectrogramData { data: vec![] };
-    let image = create_spectrogram_image(&spec_data, 100, 100, ColorScheme::Grayscale, 50.0);
-    
+    let spec_data = SpectrogramData { data: vec![], sample_rate: 44100, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
+    let params = RenderParams {
+        width: 100,
+        height: 100,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+    let image = create_spectrogram_image(&spec_data, &params);
+
     assert_eq!(image.width(), 100);
     assert_eq!(image.height(), 100);
 }
@@ -88,15 +96,97 @@ fn test_create_spectrogram_image_with_data() {
             vec![-80.0, -70.0, -60.0],
             vec![-90.0, -50.0, -40.0],
             vec![-75.0, -65.0, -55.0],
-        ]
+        ],
+        sample_rate: 44100,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
     };
-    
-    let image = create_spectrogram_image(&spec_data, 10, 10, ColorScheme::Grayscale, 50.0);
-    
+
+    let params = RenderParams {
+        width: 10,
+        height: 10,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+    let image = create_spectrogram_image(&spec_data, &params);
+
     assert_eq!(image.width(), 10);
     assert_eq!(image.height(), 10);
 }
 
+#[test]
+fn test_create_spectrogram_image_freq_top_mirrors_default() {
+    // Asymmetric matrix: each frequency bin has a distinct level
+    let spec_data = SpectrogramData {
+        data: vec![
+            vec![-80.0, -60.0, -40.0, -20.0],
+            vec![-70.0, -50.0, -30.0, -10.0],
+        ],
+        sample_rate: 44100,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+
+    let params = RenderParams {
+        width: 4,
+        height: 8,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 80.0,
+        ..Default::default()
+    };
+    let default_image = create_spectrogram_image(&spec_data, &params);
+    let flipped_image = create_spectrogram_image(&spec_data, &RenderParams { freq_top: true, ..params.clone() });
+
+    // The flipped render must be an exact vertical mirror of the default one
+    for x in 0..params.width {
+        for y in 0..params.height {
+            assert_eq!(
+                flipped_image.get_pixel(x, y),
+                default_image.get_pixel(x, params.height - 1 - y),
+                "pixel ({}, {}) is not mirrored", x, y
+            );
+        }
+    }
+}
+
+#[test]
+fn test_diverging_zero_maps_to_central_index() {
+    // Columns with -10, 0 and +10: the zero column must get the central color
+    let spec_data = SpectrogramData {
+        data: vec![vec![-10.0], vec![0.0], vec![10.0]],
+        sample_rate: 44100,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+
+    let params = RenderParams {
+        width: 3,
+        height: 1,
+        color_scheme: ColorScheme::Diverging,
+        diverging: true,
+        ..Default::default()
+    };
+    let image = create_spectrogram_image(&spec_data, &params);
+
+    let gradient = generate_gradient_hsl(get_color_stops(&ColorScheme::Diverging));
+    let central_idx = (0.5 * (GRADIENT_SIZE as f32 - 1.0)).round() as usize;
+    let central = gradient[central_idx];
+
+    assert_eq!(*image.get_pixel(1, 0), image::Rgb([central.r, central.g, central.b]));
+    // The extremes must land on the ends of the gradient
+    let low = gradient[0];
+    let high = gradient[GRADIENT_SIZE - 1];
+    assert_eq!(*image.get_pixel(0, 0), image::Rgb([low.r, low.g, low.b]));
+    assert_eq!(*image.get_pixel(2, 0), image::Rgb([high.r, high.g, high.b]));
+}
+
 #[test]
 fn test_all_color_schemes_have_stops() {
     let schemes = [
@@ -106,10 +196,735 @@ fn test_all_color_schemes_have_stops() {
         ColorScheme::Viridis,
         ColorScheme::Synthwave,
         ColorScheme::Sunset,
+        ColorScheme::Diverging,
     ];
     
     for scheme in schemes {
-        let stops = get_color_stops(scheme);
+        let stops = get_color_stops(&scheme);
         assert!(!stops.is_empty(), "Color scheme {:?} should have color stops", scheme);
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_log_freq_scale_gives_low_tone_more_pixels() {
+    // One hot bin near DC: the log axis must devote more rows to it
+    let mut frame = vec![-200.0f32; 512];
+    frame[10] = 0.0;
+    let spec_data = SpectrogramData { data: vec![frame], sample_rate: 44100, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
+
+    let params = RenderParams {
+        width: 1,
+        height: 256,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+    let hot = |image: &image::RgbImage| {
+        image.pixels().filter(|p| p.0[0] > 128).count()
+    };
+
+    let linear = hot(&create_spectrogram_image(&spec_data, &params));
+    let log = hot(&create_spectrogram_image(
+        &spec_data,
+        &RenderParams { freq_scale: FreqScale::Log, ..params.clone() },
+    ));
+
+    assert!(linear >= 1);
+    assert!(log > linear, "log axis {} rows should exceed linear {} rows", log, linear);
+}
+
+#[test]
+fn test_custom_color_scheme_stops() {
+    let stops = vec![Color::new_rgb(0x01041B), Color::new_rgb(0x4da4d5), Color::new_rgb(0xffffff)];
+    let scheme = ColorScheme::Custom(stops.clone());
+    assert_eq!(get_color_stops(&scheme), stops.as_slice());
+
+    let gradient = generate_gradient_hsl(get_color_stops(&scheme));
+    assert_eq!(gradient[0], stops[0]);
+    assert_eq!(gradient[GRADIENT_SIZE - 1], stops[2]);
+}
+
+#[test]
+fn test_invert_colormap_reverses_gradient() {
+    let gradient = generate_gradient_hsl(get_color_stops(&ColorScheme::Grayscale));
+
+    // A single maximal value samples the top of the gradient; inverted,
+    // it must sample what used to be the bottom
+    let spec_data = SpectrogramData { data: vec![vec![0.0]], sample_rate: 44100, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 };
+    let params = RenderParams {
+        width: 1,
+        height: 1,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+
+    let normal = create_spectrogram_image(&spec_data, &params);
+    let inverted = create_spectrogram_image(
+        &spec_data,
+        &RenderParams { invert_colormap: true, ..params },
+    );
+
+    let last = gradient[GRADIENT_SIZE - 1];
+    let first = gradient[0];
+    assert_eq!(*normal.get_pixel(0, 0), image::Rgb([last.r, last.g, last.b]));
+    assert_eq!(*inverted.get_pixel(0, 0), image::Rgb([first.r, first.g, first.b]));
+}
+
+#[test]
+fn test_axes_margin_grows_image() {
+    let spec_data = SpectrogramData {
+        data: vec![vec![-40.0; 64]; 32],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 128,
+        height: 64,
+        axes: true,
+        hop_length: 256,
+        ..Default::default()
+    };
+    let image = create_spectrogram_image(&spec_data, &params);
+
+    assert_eq!(image.width(), 128 + AXIS_MARGIN_LEFT);
+    assert_eq!(image.height(), 64 + AXIS_MARGIN_BOTTOM);
+
+    // Without axes the dimensions are unchanged
+    let plain = create_spectrogram_image(&spec_data, &RenderParams { axes: false, ..params });
+    assert_eq!(plain.width(), 128);
+    assert_eq!(plain.height(), 64);
+}
+
+#[test]
+fn test_percentile_normalization_resists_spike() {
+    // A 10x10 grid of moderate values; one cell becomes a huge spike.
+    // Under percentile normalization the other 99 pixels must keep their colors.
+    // Checkerboard of -40/-20 dB: plenty of duplicates, so the percentile
+    // value itself is unaffected when one cell is replaced by the spike
+    let make_data = |spike: Option<f32>| {
+        let mut data: Vec<Vec<f32>> = (0..10)
+            .map(|x| (0..10).map(|y| if (x + y) % 2 == 0 { -40.0 } else { -20.0 }).collect())
+            .collect();
+        if let Some(value) = spike {
+            data[5][5] = value;
+        }
+        SpectrogramData { data, sample_rate: 8000, phase: None, clipped: Vec::new(), signal_type: SignalType::Real, hop_length: 512 }
+    };
+
+    let params = RenderParams {
+        width: 10,
+        height: 10,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 60.0,
+        normalize: Normalization::Percentile,
+        percentile: 90.0,
+        ..Default::default()
+    };
+
+    let clean = create_spectrogram_image(&make_data(None), &params);
+    let spiked = create_spectrogram_image(&make_data(Some(100.0)), &params);
+
+    let spike_pixel = (5u32, 10 - 1 - 5u32);
+    for (x, y, pixel) in clean.enumerate_pixels() {
+        if (x, y) == spike_pixel {
+            continue;
+        }
+        assert_eq!(*pixel, *spiked.get_pixel(x, y), "pixel ({}, {}) changed", x, y);
+    }
+
+    // Sanity check: under peak normalization the spike does shift other pixels
+    let peak_params = RenderParams { normalize: Normalization::Peak, ..params };
+    let peak_spiked = create_spectrogram_image(&make_data(Some(100.0)), &peak_params);
+    assert_ne!(clean, peak_spiked);
+}
+
+#[test]
+fn test_gamma_brightens_mid_range_values() {
+    // Two cells: the max (sets the range) and a mid-range value.
+    // Gamma 2.0 must push the mid-range pixel toward the hot end.
+    let spec_data = SpectrogramData {
+        data: vec![vec![0.0], vec![-25.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 2,
+        height: 1,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+
+    let plain = create_spectrogram_image(&spec_data, &params);
+    let bright = create_spectrogram_image(&spec_data, &RenderParams { gamma: 2.0, ..params });
+
+    // The max pixel is unaffected (1.0 stays 1.0 under any gamma)
+    assert_eq!(plain.get_pixel(0, 0), bright.get_pixel(0, 0));
+    // The mid-range pixel moves up the grayscale gradient
+    assert!(bright.get_pixel(1, 0).0[0] > plain.get_pixel(1, 0).0[0]);
+}
+
+#[test]
+fn test_column_reduce_modes() {
+    // Three columns, one bin, collapsed into a single pixel column
+    let spec_data = SpectrogramData {
+        data: vec![vec![-10.0], vec![-20.0], vec![-60.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 1,
+        height: 1,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 60.0,
+        ..Default::default()
+    };
+    let gradient = generate_gradient_hsl(get_color_stops(&ColorScheme::Grayscale));
+    let expected = |db: f32| {
+        // max_db is -10 and min_db is -70 regardless of the reduction mode
+        let idx = (((db + 70.0) / 60.0) * (GRADIENT_SIZE as f32 - 1.0)).round() as usize;
+        let c = gradient[idx];
+        image::Rgb([c.r, c.g, c.b])
+    };
+
+    let pixel = |reduce: ColumnReduce| {
+        *create_spectrogram_image(&spec_data, &RenderParams { reduce, ..params.clone() })
+            .get_pixel(0, 0)
+    };
+
+    assert_eq!(pixel(ColumnReduce::Max), expected(-10.0));
+    assert_eq!(pixel(ColumnReduce::Mean), expected(-30.0));
+    assert_eq!(pixel(ColumnReduce::Min), expected(-60.0));
+}
+
+#[test]
+fn test_bilinear_interpolation_blends_adjacent_bins() {
+    // Two bins upscaled to three rows: the middle row sits halfway between them
+    let spec_data = SpectrogramData {
+        data: vec![vec![-60.0, 0.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 1,
+        height: 3,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 60.0,
+        ..Default::default()
+    };
+
+    let nearest = create_spectrogram_image(&spec_data, &params);
+    let smooth = create_spectrogram_image(&spec_data, &RenderParams { interpolate: true, ..params });
+
+    // Nearest-neighbor snaps the middle row to one of the two bin colors
+    let middle = *nearest.get_pixel(0, 1);
+    assert!(middle == *nearest.get_pixel(0, 0) || middle == *nearest.get_pixel(0, 2));
+
+    // Bilinear produces an intermediate color strictly between the two bins
+    let low = smooth.get_pixel(0, 2).0[0];
+    let mid = smooth.get_pixel(0, 1).0[0];
+    let high = smooth.get_pixel(0, 0).0[0];
+    assert!(low < mid && mid < high, "expected {} < {} < {}", low, mid, high);
+}
+
+#[test]
+fn test_time_y_orientation_rotates_features() {
+    // One hot bin across all columns: a horizontal line in TimeX must become
+    // a vertical line in TimeY, with the output dimensions swapped
+    let spec_data = SpectrogramData {
+        data: vec![vec![-80.0, 0.0, -80.0]; 4],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 4,
+        height: 3,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 50.0,
+        ..Default::default()
+    };
+
+    let time_x = create_spectrogram_image(&spec_data, &params);
+    let time_y = create_spectrogram_image(
+        &spec_data,
+        &RenderParams { orientation: Orientation::TimeY, ..params },
+    );
+
+    assert_eq!((time_y.width(), time_y.height()), (3, 4));
+
+    let hot = |p: &image::Rgb<u8>| p.0[0] > 128;
+    // Horizontal line in the middle row of TimeX
+    assert!((0..4).all(|x| hot(time_x.get_pixel(x, 1))));
+    // Vertical line in the middle column of TimeY
+    assert!((0..4).all(|y| hot(time_y.get_pixel(1, y))));
+    assert!((0..4).all(|y| !hot(time_y.get_pixel(0, y)) && !hot(time_y.get_pixel(2, y))));
+}
+
+#[test]
+fn test_auto_range_is_tighter_than_default_on_tone_over_noise() {
+    // Eight noise bins at -50 dB under a 0 dB tone: the median noise floor
+    // gives an effective range of 56 dB, far tighter than the 110 dB default,
+    // so the noise renders much closer to the bottom of the gradient
+    let mut bins = vec![-50.0; 8];
+    bins.push(0.0);
+    let spec_data = SpectrogramData {
+        data: vec![bins],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 1,
+        height: 9,
+        color_scheme: ColorScheme::Grayscale,
+        ..Default::default()
+    };
+
+    let fixed = create_spectrogram_image(&spec_data, &params);
+    let auto = create_spectrogram_image(
+        &spec_data,
+        &RenderParams { auto_range: true, ..params },
+    );
+
+    // The tone (top row) saturates the gradient either way
+    assert_eq!(auto.get_pixel(0, 0).0[0], fixed.get_pixel(0, 0).0[0]);
+
+    // Noise: (−50 + 56) / 56 ≈ 0.11 with auto vs (−50 + 110) / 110 ≈ 0.55 fixed
+    let auto_noise = auto.get_pixel(0, 8).0[0];
+    let fixed_noise = fixed.get_pixel(0, 8).0[0];
+    assert!(
+        auto_noise < fixed_noise / 2,
+        "expected auto noise {} well below fixed noise {}",
+        auto_noise,
+        fixed_noise
+    );
+}
+
+#[test]
+fn test_freq_crop_excludes_out_of_band_bins() {
+    // 9 bins at 8 kHz (1 kHz Nyquist/... 500 Hz per bin): a hot bin at
+    // 0 Hz and one at 4 kHz, with the band of interest between them
+    let mut bins = vec![-90.0; 9];
+    bins[0] = 0.0; // 0 Hz
+    bins[4] = -20.0; // 2 kHz, in-band
+    bins[8] = 0.0; // 4 kHz
+    let spec_data = SpectrogramData {
+        data: vec![bins],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 1,
+        height: 5,
+        color_scheme: ColorScheme::Grayscale,
+        dynamic_range: 100.0,
+        freq_min: Some(1500.0),
+        freq_max: Some(2500.0),
+        ..Default::default()
+    };
+
+    let cropped = create_spectrogram_image(&spec_data, &params);
+
+    // Only bins 3..=5 remain; the 0 dB bins at the band edges are gone,
+    // so the brightest pixel is the -20 dB tone, not a saturated white
+    let max_channel = cropped.pixels().map(|p| p.0[0]).max().unwrap();
+    let full = create_spectrogram_image(
+        &spec_data,
+        &RenderParams { freq_min: None, freq_max: None, ..params },
+    );
+    let full_max = full.pixels().map(|p| p.0[0]).max().unwrap();
+    assert_eq!(full_max, 255);
+    assert!(max_channel < 255, "cropped image still shows out-of-band peak");
+
+    // The in-band tone is still present and brighter than the noise floor
+    let min_channel = cropped.pixels().map(|p| p.0[0]).min().unwrap();
+    assert!(max_channel > min_channel);
+}
+
+#[test]
+fn test_frame_tiles_count_and_fixed_size() {
+    // 100 frames at hop 512 / 8 kHz; 1-second tiles cover
+    // round(8000 / 512) = 16 frames each, so ceil(100 / 16) = 7 tiles
+    let spec_data = SpectrogramData {
+        data: vec![vec![-30.0; 64]; 100],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 160,
+        height: 64,
+        hop_length: 512,
+        ..Default::default()
+    };
+    let dir = std::env::temp_dir().join("sgvr_test_frames");
+    std::fs::remove_dir_all(&dir).ok();
+
+    let written = write_frame_tiles(&spec_data, &params, &dir, 1.0).unwrap();
+
+    assert_eq!(written, 7);
+    for i in 0..written {
+        let img = image::open(dir.join(format!("frame_{:06}.png", i))).unwrap();
+        // Every tile has the same dimensions, including the last partial one
+        assert_eq!((img.width(), img.height()), (160, 64));
+    }
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_rgb_and_lab_black_to_white_midpoints_are_gray() {
+    let stops = [Color::new(0, 0, 0), Color::new(255, 255, 255)];
+    for space in [InterpSpace::Rgb, InterpSpace::Lab] {
+        let mid = generate_gradient(&stops, space)[GRADIENT_SIZE / 2];
+        assert_eq!(mid.r, mid.g, "{:?} midpoint {:?}", space, mid);
+        assert_eq!(mid.g, mid.b, "{:?} midpoint {:?}", space, mid);
+    }
+    // RGB interpolation is linear in the components themselves
+    let mid = generate_gradient(&stops, InterpSpace::Rgb)[GRADIENT_SIZE / 2];
+    assert!((mid.r as i32 - 128).abs() <= 1, "midpoint {:?}", mid);
+}
+
+#[test]
+fn test_hsl_and_rgb_gradients_differ_between_hues() {
+    let stops = [Color::new(0, 0, 255), Color::new(255, 0, 0)];
+    let rgb = generate_gradient(&stops, InterpSpace::Rgb)[GRADIENT_SIZE / 2];
+    let hsl = generate_gradient(&stops, InterpSpace::Hsl)[GRADIENT_SIZE / 2];
+
+    // Component-wise blending passes through dim purple...
+    assert_eq!(rgb.g, 0);
+    assert!((rgb.r as i32 - 128).abs() <= 1 && (rgb.b as i32 - 128).abs() <= 1);
+    // ...while the HSL hue path stays fully saturated (magenta)
+    assert!(hsl.r > 200 && hsl.b > 200, "hsl midpoint {:?}", hsl);
+}
+
+#[test]
+fn test_hsl_hue_interpolation_wraps_across_zero() {
+    // 350° and 10° are both near-red; the short hue path crosses 0°, so the
+    // midpoint must stay red rather than swinging through cyan at 180°
+    let stops = [
+        Color::new(255, 0, 42),  // h = 350°
+        Color::new(255, 42, 0),  // h = 10°
+    ];
+    let mid = generate_gradient(&stops, InterpSpace::Hsl)[GRADIENT_SIZE / 2];
+    let HSL { h, .. } = HSL::from_rgb(&[mid.r, mid.g, mid.b]);
+    assert!(
+        !(90.0..270.0).contains(&h) && mid.r > 200 && mid.g < 60 && mid.b < 60,
+        "midpoint {:?} (h = {:.1}°) is not red",
+        mid,
+        h
+    );
+}
+
+#[test]
+fn test_render_to_rgb_bytes_length_matches_dimensions() {
+    let spec_data = SpectrogramData {
+        data: vec![vec![-30.0; 64]; 32],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 100, height: 50, ..Default::default() };
+
+    let (bytes, width, height) = render_to_rgb_bytes(&spec_data, &params);
+    assert_eq!((width, height), (100, 50));
+    assert_eq!(bytes.len(), 100 * 50 * 3);
+
+    // The PNG encoder produces a parseable image with the same dimensions
+    let png = encode_png_bytes(&create_spectrogram_image(&spec_data, &params)).unwrap();
+    let decoded = image::load_from_memory(&png).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (100, 50));
+}
+
+#[test]
+fn test_floor_db_maps_sub_floor_values_to_bottom_color() {
+    // Bin 0 sits well below the floor, bin 1 well above it
+    let spec_data = SpectrogramData {
+        data: vec![vec![-90.0, -20.0]; 8],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 8,
+        height: 2,
+        floor_db: Some(-60.0),
+        ..Default::default()
+    };
+
+    let img = render_spectrogram(&spec_data, &params);
+    let bottom = get_color_stops(&params.color_scheme)[0];
+    for x in 0..img.width() {
+        // Bin 0 renders at the bottom row
+        assert_eq!(
+            *img.get_pixel(x, 1),
+            Rgb([bottom.r, bottom.g, bottom.b]),
+            "sub-floor pixel at x = {} is not the gradient's bottom color",
+            x
+        );
+        // -20 dB is inside the default 110 dB range, so the above-floor
+        // row must come out brighter than the background
+        assert_ne!(*img.get_pixel(x, 0), Rgb([bottom.r, bottom.g, bottom.b]));
+    }
+}
+
+#[test]
+fn test_nan_cell_does_not_poison_normalization() {
+    // One NaN bin amid a normal tone; the rest of the image must still be
+    // normalized off the finite values and the NaN pixel maps to the
+    // bottom color instead of propagating
+    let mut data = vec![vec![-80.0, -20.0, -80.0, -80.0]; 4];
+    data[1][2] = f32::NAN;
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 4, height: 4, ..Default::default() };
+
+    let img = render_spectrogram(&spec_data, &params);
+    let bottom = get_color_stops(&params.color_scheme)[0];
+
+    // The NaN cell (bin 2, column 1) renders as the floor color
+    assert_eq!(*img.get_pixel(1, 1), Rgb([bottom.r, bottom.g, bottom.b]));
+    // -20 dB is the finite maximum, so the tone row gets the top color
+    let top = get_color_stops(&params.color_scheme).last().unwrap();
+    assert_eq!(*img.get_pixel(0, 2), Rgb([top.r, top.g, top.b]));
+}
+
+#[test]
+fn test_clip_color_marks_saturated_cells_only() {
+    // Frame 0 is flagged as clipped, frame 1 is clean; both carry the
+    // same loud bin, so only the flagged frame's peak changes color
+    let spec_data = SpectrogramData {
+        data: vec![vec![-80.0, -1.0], vec![-80.0, -1.0]],
+        sample_rate: 8000,
+        phase: None,
+        clipped: vec![true, false],
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let clip = Color::new(255, 0, 255);
+    let params = RenderParams {
+        width: 2,
+        height: 2,
+        clip_color: Some(clip),
+        ..Default::default()
+    };
+
+    let img = render_spectrogram(&spec_data, &params);
+    let clip_px = Rgb([clip.r, clip.g, clip.b]);
+    assert_eq!(*img.get_pixel(0, 0), clip_px, "peak cell of the clipped frame");
+    assert_ne!(*img.get_pixel(1, 0), clip_px, "peak cell of the clean frame");
+    assert_ne!(*img.get_pixel(0, 1), clip_px, "quiet cell of the clipped frame");
+}
+
+#[test]
+fn test_pixel_to_data_coords_inverts_render_mapping() {
+    // 4 frames x 8 bins rendered 1:1, with a marker in frame 1, bin 5
+    let mut data = vec![vec![-60.0f32; 8]; 4];
+    data[1][5] = -3.0;
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 4, height: 8, ..Default::default() };
+
+    // Bin 5 renders at row height - 1 - 5 = 2 with the default bottom-up axis
+    let (time_s, freq_hz, db) = pixel_to_data_coords(&spec_data, &params, 1, 2).unwrap();
+    assert_eq!(db, -3.0);
+    assert!((time_s - 512.0 / 8000.0).abs() < 1e-6);
+    assert_eq!(freq_hz, spec_data.bin_frequencies()[5]);
+    // The value round-trips through the data-side query
+    assert_eq!(spec_data.sample_at(time_s, freq_hz), Some(-3.0));
+
+    // Out-of-image pixels map to nothing
+    assert_eq!(pixel_to_data_coords(&spec_data, &params, 4, 0), None);
+    assert_eq!(pixel_to_data_coords(&spec_data, &params, 0, 8), None);
+}
+
+#[test]
+fn test_iq_dual_stacks_two_panes() {
+    let mag = SpectrogramData {
+        data: vec![vec![-30.0f32; 16]; 8],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Iq,
+        hop_length: 512,
+    };
+    let inst = SpectrogramData {
+        data: vec![vec![0.0f32; 16]; 7],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Iq,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 8, height: 16, ..Default::default() };
+
+    let img = render_iq_dual(&mag, &inst, &params);
+    assert_eq!(img.width(), 8);
+    assert_eq!(img.height(), 16 * 2 + 2);
+    // An all-zero deviation pane renders at the diverging midpoint (near-white)
+    let Rgb([r, g, b]) = *img.get_pixel(0, 16 + 2);
+    assert!(r > 250 && g > 250 && b > 250, "midpoint pixel ({}, {}, {}) is not near-white", r, g, b);
+}
+
+#[test]
+fn test_silent_file_renders_single_color_without_nan() {
+    // Every cell on the same level makes max_db == min_db once the range
+    // degenerates; this must collapse to one color instead of dividing by zero
+    let spec_data = SpectrogramData {
+        data: vec![vec![-180.0f32; 8]; 8],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams {
+        width: 8,
+        height: 8,
+        dynamic_range: 0.0,
+        ..Default::default()
+    };
+
+    let img = render_spectrogram(&spec_data, &params);
+    let bottom = get_color_stops(&params.color_scheme)[0];
+    assert!(
+        img.pixels().all(|p| *p == Rgb([bottom.r, bottom.g, bottom.b])),
+        "a degenerate range must render uniformly in the bottom color"
+    );
+}
+
+#[test]
+fn test_single_thread_render_matches_default_pool() {
+    let mut data = vec![vec![-60.0f32; 64]; 48];
+    for (i, frame) in data.iter_mut().enumerate() {
+        frame[i % 64] = -10.0;
+    }
+    let spec_data = SpectrogramData {
+        data,
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 96, height: 64, ..Default::default() };
+
+    let default_render = render_spectrogram(&spec_data, &params);
+    let single = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(|| render_spectrogram(&spec_data, &params));
+
+    assert_eq!(default_render.as_raw(), single.as_raw());
+}
+
+#[test]
+fn test_title_grows_image_by_caption_height() {
+    let spec_data = SpectrogramData {
+        data: vec![vec![-40.0; 33]; 16],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    let params = RenderParams { width: 64, height: 32, ..Default::default() };
+    let plain = create_spectrogram_image(&spec_data, &params);
+
+    let titled_params = RenderParams { title: Some("test.wav".to_string()), ..params };
+    let titled = create_spectrogram_image(&spec_data, &titled_params);
+
+    assert_eq!(titled.width(), plain.width());
+    assert_eq!(titled.height(), plain.height() + CAPTION_HEIGHT);
+    // The caption bar is not blank: the text lights up some pixels
+    let lit = (0..titled.width())
+        .flat_map(|x| (0..CAPTION_HEIGHT).map(move |y| (x, y)))
+        .filter(|&(x, y)| titled.get_pixel(x, y).0 != [0, 0, 0])
+        .count();
+    assert!(lit > 0);
+}
+
+#[test]
+fn test_gradient_cache_returns_identical_tables() {
+    let first = cached_gradient(&ColorScheme::Viridis, false, InterpSpace::Hsl);
+    let second = cached_gradient(&ColorScheme::Viridis, false, InterpSpace::Hsl);
+    assert_eq!(first, second);
+
+    // The cached table matches what a fresh generation would produce,
+    // inversion included
+    let mut fresh = generate_gradient(get_color_stops(&ColorScheme::Viridis), InterpSpace::Hsl);
+    fresh.reverse();
+    assert_eq!(cached_gradient(&ColorScheme::Viridis, true, InterpSpace::Hsl), fresh);
+}
+
+#[test]
+fn test_iq_split_imbalance_shows_as_panel_brightness() {
+    let make = |level: f32| SpectrogramData {
+        data: vec![vec![level; 33]; 16],
+        sample_rate: 8000,
+        phase: None,
+        clipped: Vec::new(),
+        signal_type: SignalType::Real,
+        hop_length: 512,
+    };
+    // I is 30 dB louder than Q
+    let i_data = make(-10.0);
+    let q_data = make(-40.0);
+    let params = RenderParams { width: 32, height: 20, dynamic_range: 60.0, ..Default::default() };
+
+    let img = render_iq_split(&i_data, &q_data, &params);
+    assert_eq!(img.height(), 2 * 20 + 2);
+
+    let mean = |y0: u32, y1: u32| {
+        let mut sum = 0u64;
+        for y in y0..y1 {
+            for x in 0..img.width() {
+                let Rgb([r, g, b]) = *img.get_pixel(x, y);
+                sum += r as u64 + g as u64 + b as u64;
+            }
+        }
+        sum / ((y1 - y0) as u64 * img.width() as u64)
+    };
+
+    // Shared normalization: the quieter Q panel renders visibly dimmer
+    assert!(mean(0, 20) > mean(22, 42) + 50);
+}